@@ -0,0 +1,281 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! A rectangle of things, like the map of a cave or a forest, looked up by (x, y).
+//!
+//! x goes to the right and y goes down, like reading a puzzle input.  Coordinates are signed and
+//! the top left corner doesn't have to be (0, 0), so a map can grow out from wherever it
+//! started.
+
+use crate::{numbered_lines, ParseError};
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+/// A spot on a grid, (x, y).
+pub type Point = (i64, i64);
+
+/// The steps to the four points next to a point, in reading order: up, left, right, down.
+pub const NEIGHBORS_4: [Point; 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// The steps to the eight points around a point, in reading order.
+pub const NEIGHBORS_8: [Point; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Orders points the way you'd read them: top to bottom, then left to right.
+pub fn reading_order(a: &Point, b: &Point) -> Ordering {
+    (a.1, a.0).cmp(&(b.1, b.0))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    /// The top left corner.
+    min: Point,
+    width: usize,
+    height: usize,
+    /// The cells, a row at a time.
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// A grid `width` by `height` with its top left corner at `min`, full of `fill`.
+    pub fn new(min: Point, width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            min,
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Reads a grid from text, one row per line, with its top left corner at (0, 0).  `cell`
+    /// turns each character into a cell, or says what's wrong with it.  Every row has to be the
+    /// same length.
+    pub fn parse<F>(input: &str, mut cell: F) -> Result<Grid<T>, ParseError>
+    where
+        F: FnMut(char) -> Result<T, &'static str>,
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (line_no, line) in numbered_lines(input) {
+            for (idx, c) in line.char_indices() {
+                let here = &line[idx..idx + c.len_utf8()];
+                cells.push(cell(c).map_err(|msg| ParseError::at(line_no, line, here, msg))?);
+            }
+            let len = line.chars().count();
+            if *width.get_or_insert(len) != len {
+                return Err(ParseError::line(
+                    line_no,
+                    line,
+                    "the rows aren't all the same length",
+                ));
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            min: (0, 0),
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The top left corner.
+    pub fn min(&self) -> Point {
+        self.min
+    }
+
+    /// The bottom right corner.
+    pub fn max(&self) -> Point {
+        (
+            self.min.0 + self.width as i64 - 1,
+            self.min.1 + self.height as i64 - 1,
+        )
+    }
+
+    fn index_of(&self, p: Point) -> Option<usize> {
+        let (x, y) = (p.0 - self.min.0, p.1 - self.min.1);
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        Some(y as usize * self.width + x as usize)
+    }
+
+    fn point_at(&self, idx: usize) -> Point {
+        (
+            self.min.0 + (idx % self.width) as i64,
+            self.min.1 + (idx / self.width) as i64,
+        )
+    }
+
+    /// Whether `p` is on the grid.
+    pub fn contains(&self, p: Point) -> bool {
+        self.index_of(p).is_some()
+    }
+
+    /// The cell at `p`, or None if it's off the grid.
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index_of(p).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.index_of(p).map(move |idx| &mut self.cells[idx])
+    }
+
+    /// All the points on the grid, in reading order.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.cells.len()).map(move |idx| self.point_at(idx))
+    }
+
+    /// All the cells with where they are, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(idx, cell)| (self.point_at(idx), cell))
+    }
+
+    /// A grid the same shape as this one, with `f` of each cell.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            min: self.min,
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// The rows of the grid, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The points up, left, right and down from `p` that are on the grid, in that order.
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbors(p, &NEIGHBORS_4)
+    }
+
+    /// The points around `p` that are on the grid, in reading order.
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbors(p, &NEIGHBORS_8)
+    }
+
+    fn neighbors<'a>(&'a self, p: Point, steps: &'a [Point]) -> impl Iterator<Item = Point> + 'a {
+        steps
+            .iter()
+            .map(move |d| (p.0 + d.0, p.1 + d.1))
+            .filter(move |&n| self.contains(n))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        match self.index_of(p) {
+            Some(idx) => &self.cells[idx],
+            None => panic!("{:?} is off the grid", p),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        match self.index_of(p) {
+            Some(idx) => &mut self.cells[idx],
+            None => panic!("{:?} is off the grid", p),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = Grid::parse("#.#\n..#\n", |c| match c {
+            '#' | '.' => Ok(c),
+            _ => Err("not a wall or floor"),
+        })
+        .unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&'.'), grid.get((1, 0)));
+        assert_eq!('#', grid[(2, 1)]);
+        assert_eq!(None, grid.get((3, 0)));
+        assert_eq!(None, grid.get((0, -1)));
+        assert_eq!("#.#\n..#\n", grid.to_string());
+
+        let err = Grid::parse("#.#\n.x#", |c| match c {
+            '#' | '.' => Ok(c),
+            _ => Err("not a wall or floor"),
+        })
+        .unwrap_err();
+        assert_eq!((2, 2), (err.line, err.column));
+        let err = Grid::parse("#.#\n.#", Ok).unwrap_err();
+        assert_eq!(
+            "2:1: the rows aren't all the same length: \".#\"",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_signed_coordinates() {
+        let mut grid = Grid::new((-2, -1), 4, 3, 0);
+        assert_eq!((1, 1), grid.max());
+        grid[(-2, -1)] = 1;
+        grid[(1, 1)] = 2;
+        assert_eq!(vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2], grid.cells);
+        assert_eq!(Some((-2, -1)), grid.points().next());
+        assert!(!grid.contains((2, 0)));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new((0, 0), 3, 3, ());
+        assert_eq!(
+            vec![(1, 0), (0, 1), (2, 1), (1, 2)],
+            grid.neighbors4((1, 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 0), (0, 1), (1, 1)],
+            grid.neighbors8((0, 0)).collect::<Vec<_>>()
+        );
+        let mut points = vec![(2, 0), (0, 1), (1, 0)];
+        points.sort_by(reading_order);
+        assert_eq!(vec![(1, 0), (2, 0), (0, 1)], points);
+    }
+}
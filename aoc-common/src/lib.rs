@@ -0,0 +1,194 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Helpers shared between all the days.
+
+pub mod grid;
+pub mod logging;
+mod parse;
+pub mod path;
+pub mod synthetic;
+
+pub use crate::parse::{numbered_lines, parse_number, parse_numbers, ErrorKind, ParseError};
+
+use std::{convert::TryFrom, env, fmt, fs, io, process, str::FromStr};
+
+/// The answer to one part of a puzzle, and anything else worth knowing about how we got there.
+pub struct Answer {
+    value: Box<dyn fmt::Display>,
+    stats: Vec<(&'static str, Stat)>,
+}
+
+impl Answer {
+    pub fn new<T: fmt::Display + 'static>(value: T) -> Answer {
+        Answer {
+            value: Box::new(value),
+            stats: Vec::new(),
+        }
+    }
+
+    /// Adds a stat about the answer, like how many rounds a battle took or who won it.
+    pub fn with_stat<S: Into<Stat>>(mut self, name: &'static str, stat: S) -> Answer {
+        self.stats.push((name, stat.into()));
+        self
+    }
+
+    /// The stats, in the order they were added.
+    pub fn stats(&self) -> &[(&'static str, Stat)] {
+        &self.stats
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// A number or a bit of text that goes along with an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stat {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stat::Number(n) => write!(f, "{}", n),
+            Stat::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+macro_rules! number_stat {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Stat {
+            fn from(n: $t) -> Stat {
+                i64::try_from(n)
+                    .map(Stat::Number)
+                    .unwrap_or_else(|_| Stat::Text(n.to_string()))
+            }
+        })*
+    };
+}
+
+number_stat!(i32, i64, u8, u32, u64, usize);
+
+impl From<char> for Stat {
+    fn from(c: char) -> Stat {
+        Stat::Text(c.to_string())
+    }
+}
+
+impl From<&str> for Stat {
+    fn from(s: &str) -> Stat {
+        Stat::Text(s.to_string())
+    }
+}
+
+impl From<String> for Stat {
+    fn from(s: String) -> Stat {
+        Stat::Text(s)
+    }
+}
+
+/// A solution to one day's puzzle.  Each part gets the whole puzzle input as text, and fails if
+/// it can't make sense of it.
+pub trait Solution {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError>;
+    fn part2(&self, input: &str) -> Result<Answer, ParseError>;
+
+    /// Solves part `part` (1 or 2) of the puzzle.
+    fn part(&self, part: u8, input: &str) -> Result<Answer, ParseError> {
+        match part {
+            1 => self.part1(input),
+            2 => self.part2(input),
+            _ => panic!("Puzzles only have two parts, not {}", part),
+        }
+    }
+}
+
+/// Reads `input.txt` from the current directory and prints both answers.
+/// This is what each day's own binary runs.  It takes `-q`, `-v` and `-vv` to say how much the
+/// day should log along the way.
+pub fn run_main(solution: &dyn Solution) -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (level, _) = logging::take_verbosity(&args);
+    logging::init(level);
+    let input = fs::read_to_string("input.txt")?;
+    for part in 1..=2 {
+        match solution.part(part, &input) {
+            Ok(answer) => println!("Part {}: {}", part, answer),
+            Err(e) => {
+                eprintln!("{}", e.in_file("input.txt"));
+                process::exit(1);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns all the numbers that appear in `s`, in order.
+///
+/// A `-` is treated as a sign only if it comes right before a digit and isn't
+/// right after one, so "pos=<-3,4>" gives [-3, 4] but "1518-11-01" gives
+/// [1518, 11, 1].  A number too big for `T` is an error.
+pub fn numbers_in_string<T: FromStr>(s: &str) -> Result<Vec<T>, T::Err> {
+    number_spans(s)
+        .into_iter()
+        .map(|(start, end)| s[start..end].parse())
+        .collect()
+}
+
+/// Finds the byte ranges of the numbers in `s`.
+pub(crate) fn number_spans(s: &str) -> Vec<(usize, usize)> {
+    let bytes = s.as_bytes();
+    let mut spans = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let negative = bytes[idx] == b'-'
+            && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit)
+            && (idx == 0 || !bytes[idx - 1].is_ascii_digit());
+        if !negative && !bytes[idx].is_ascii_digit() {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        idx += 1;
+        while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+            idx += 1;
+        }
+        spans.push((start, idx));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_unsigned() {
+        let nums: Vec<u32> = numbers_in_string("#123 @ 3,2: 5x4").unwrap();
+        assert_eq!(vec![123, 3, 2, 5, 4], nums);
+        let nums: Vec<u32> =
+            numbers_in_string("[1518-11-01 00:05] Guard #10 begins shift").unwrap();
+        assert_eq!(vec![1518, 11, 1, 0, 5, 10], nums);
+        let nums: Vec<u32> = numbers_in_string("no numbers here").unwrap();
+        assert!(nums.is_empty());
+    }
+
+    #[test]
+    fn test_numbers_signed() {
+        let nums: Vec<i32> = numbers_in_string("position=<-3,  6> velocity=< 2, -1>").unwrap();
+        assert_eq!(vec![-3, 6, 2, -1], nums);
+        let nums: Vec<i64> = numbers_in_string("pos=<0,-12,3>, r=4").unwrap();
+        assert_eq!(vec![0, -12, 3, 4], nums);
+        let nums: Vec<i64> = numbers_in_string("-1,2,-3,4").unwrap();
+        assert_eq!(vec![-1, 2, -3, 4], nums);
+        let nums: Vec<u16> = numbers_in_string("y=13, x=498..504").unwrap();
+        assert_eq!(vec![13, 498, 504], nums);
+        assert!(numbers_in_string::<u8>("1 2 300").is_err());
+    }
+}
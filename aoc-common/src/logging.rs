@@ -0,0 +1,82 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Logging for the days, so they can say what they're doing without it getting mixed up with the
+//! answers.  The days use the `log` macros, and this writes whatever is turned on to stderr:
+//!
+//! * `-q` only errors
+//! * by default, what the days found along the way (`info!`)
+//! * `-v` every step they take (`debug!`)
+//! * `-vv` everything, down to every marble and every army's attack (`trace!`)
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Takes the verbosity flags out of `args`, and returns how much to log along with the rest of
+/// the args.  The last flag wins.
+pub fn take_verbosity(args: &[String]) -> (LevelFilter, Vec<String>) {
+    let mut level = LevelFilter::Info;
+    let mut rest = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-q" | "--quiet" => level = LevelFilter::Error,
+            "-v" | "--verbose" => level = LevelFilter::Debug,
+            "-vv" => level = LevelFilter::Trace,
+            _ => rest.push(arg.clone()),
+        }
+    }
+    (level, rest)
+}
+
+/// Starts logging to stderr at `level`.  Only the first call sets the logger; later calls just
+/// change the level.
+pub fn init(level: LevelFilter) {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_take_verbosity() {
+        assert_eq!(
+            (LevelFilter::Info, args("run --day 15")),
+            take_verbosity(&args("run --day 15"))
+        );
+        assert_eq!(
+            (LevelFilter::Error, args("run")),
+            take_verbosity(&args("-q run"))
+        );
+        assert_eq!(
+            (LevelFilter::Debug, args("run --day 7")),
+            take_verbosity(&args("run -v --day 7"))
+        );
+        assert_eq!(
+            (LevelFilter::Trace, args("run")),
+            take_verbosity(&args("run -vv"))
+        );
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Errors for puzzle input that doesn't look like we expect, or that can't be solved, and helpers
+//! to make them.
+
+use std::{error, fmt, str::FromStr};
+
+/// Whether it was the input that was wrong, or solving the puzzle with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The input doesn't look like we expect.
+    Input,
+    /// The input read fine, but solving the puzzle with it didn't work, like a program that
+    /// faulted or a search that found nothing.  There's no line or text to point at.
+    Fault,
+}
+
+/// Something wrong with the puzzle input, and where it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    /// The input file, if we know it.  The days only see the text, so the runner fills it in.
+    pub file: Option<String>,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column of the start of `text` in the line, starting at 1.
+    pub column: usize,
+    /// The text that we couldn't make sense of.
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, text: &str, message: &str) -> ParseError {
+        ParseError {
+            kind: ErrorKind::Input,
+            file: None,
+            line,
+            column,
+            text: text.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// An error from solving the puzzle rather than reading it.
+    pub fn fault(message: &str) -> ParseError {
+        ParseError {
+            kind: ErrorKind::Fault,
+            ..ParseError::new(0, 0, "", message)
+        }
+    }
+
+    /// An error about `text` in `line`.  If `text` is a slice of `line` the column is where it
+    /// starts, otherwise it is where it's first found, or the start of the line.
+    pub fn at(line_no: usize, line: &str, text: &str, message: &str) -> ParseError {
+        let line_start = line.as_ptr() as usize;
+        let text_start = text.as_ptr() as usize;
+        let offset =
+            if text_start >= line_start && text_start + text.len() <= line_start + line.len() {
+                text_start - line_start
+            } else {
+                line.find(text).unwrap_or(0)
+            };
+        let column = line[..offset].chars().count() + 1;
+        ParseError::new(line_no, column, text, message)
+    }
+
+    /// An error about the whole of `line`.
+    pub fn line(line_no: usize, line: &str, message: &str) -> ParseError {
+        ParseError::new(line_no, 1, line, message)
+    }
+
+    /// Records which file the input came from.
+    pub fn in_file(mut self, file: &str) -> ParseError {
+        self.file = Some(file.to_string());
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        if self.kind == ErrorKind::Fault {
+            if self.file.is_some() {
+                write!(f, " ")?;
+            }
+            return write!(f, "{}", self.message);
+        }
+        write!(
+            f,
+            "{}:{}: {}: {:?}",
+            self.line, self.column, self.message, self.text
+        )
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// The lines of `input`, numbered from 1.
+pub fn numbered_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.lines().enumerate().map(|(idx, line)| (idx + 1, line))
+}
+
+/// Parses `text`, which is part of `line`, into a number.
+pub fn parse_number<T: FromStr>(line_no: usize, line: &str, text: &str) -> Result<T, ParseError> {
+    text.trim()
+        .parse()
+        .map_err(|_| ParseError::at(line_no, line, text, "not a number"))
+}
+
+/// Returns the numbers in `line`, like `numbers_in_string`, but there has to be exactly `count`
+/// of them.
+pub fn parse_numbers<T: FromStr>(
+    line_no: usize,
+    line: &str,
+    count: usize,
+) -> Result<Vec<T>, ParseError> {
+    let spans = crate::number_spans(line);
+    if spans.len() != count {
+        return Err(ParseError::line(
+            line_no,
+            line,
+            &format!("expected {} numbers, found {}", count, spans.len()),
+        ));
+    }
+    spans
+        .into_iter()
+        .map(|(start, end)| {
+            line[start..end].parse().map_err(|_| {
+                ParseError::at(line_no, line, &line[start..end], "number out of range")
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numbers() {
+        let line = "#123 @ 3,2: 5x4";
+        assert_eq!(Ok(vec![123, 3, 2, 5, 4]), parse_numbers::<u32>(1, line, 5));
+        assert_eq!(
+            Err(ParseError::new(7, 1, line, "expected 4 numbers, found 5")),
+            parse_numbers::<u32>(7, line, 4)
+        );
+        assert_eq!(
+            Err(ParseError::new(2, 5, "300", "number out of range")),
+            parse_numbers::<u8>(2, "1 2 300", 3)
+        );
+    }
+
+    #[test]
+    fn test_error_position() {
+        let line = "Step C must be finished before step A can begin.";
+        let err = ParseError::at(3, line, &line[5..6], "bad step");
+        assert_eq!(6, err.column);
+        assert_eq!("3:6: bad step: \"C\"", err.to_string());
+        let err = parse_number::<i32>(4, "+1x", "+1x").unwrap_err();
+        assert_eq!(1, err.column);
+        assert_eq!(
+            "input.txt:4:1: not a number: \"+1x\"",
+            err.in_file("input.txt").to_string()
+        );
+    }
+
+    #[test]
+    fn test_fault() {
+        let err = ParseError::fault("instruction 3 overflowed");
+        assert_eq!(ErrorKind::Fault, err.kind);
+        assert_eq!("instruction 3 overflowed", err.to_string());
+        assert_eq!(
+            "input.txt: instruction 3 overflowed",
+            err.in_file("input.txt").to_string()
+        );
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Shortest paths through anything that can say what's next to a node.
+//!
+//! The searches take a `neighbors` closure which gives the nodes you can get to from a node, and
+//! for Dijkstra and A* how long it takes to get there.  They all give back the distances and the
+//! way back to the start, so the path to any node they reached can be put back together.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// How far the search got, and how it got there.
+#[derive(Debug, Clone)]
+pub struct Paths<N> {
+    start: N,
+    dist: HashMap<N, usize>,
+    /// The node before each node on a shortest path to it.
+    prev: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash> Paths<N> {
+    fn new(start: N) -> Paths<N> {
+        let mut dist = HashMap::new();
+        dist.insert(start.clone(), 0);
+        Paths {
+            start,
+            dist,
+            prev: HashMap::new(),
+        }
+    }
+
+    /// The shortest distance from the start to `to`, if the search reached it.
+    pub fn distance(&self, to: &N) -> Option<usize> {
+        self.dist.get(to).cloned()
+    }
+
+    /// The shortest distance to every node the search reached, including the start.
+    pub fn distances(&self) -> &HashMap<N, usize> {
+        &self.dist
+    }
+
+    /// Just the distances, when the paths don't matter.
+    pub fn into_distances(self) -> HashMap<N, usize> {
+        self.dist
+    }
+
+    /// The nodes on a shortest path from the start to `to`, including both of them.
+    pub fn path_to(&self, to: &N) -> Option<Vec<N>> {
+        if !self.dist.contains_key(to) {
+            return None;
+        }
+        let mut path = vec![to.clone()];
+        while path.last() != Some(&self.start) {
+            path.push(self.prev[path.last().unwrap()].clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth first search from `start`, for when every step is the same length.  Nodes found at
+/// the same distance keep the path through whichever neighbor came first.
+pub fn bfs<N, F, I>(start: N, mut neighbors: F) -> Paths<N>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new(start.clone());
+    let mut open = VecDeque::new();
+    open.push_back(start);
+    while let Some(u) = open.pop_front() {
+        let next_dist = paths.dist[&u] + 1;
+        for v in neighbors(&u) {
+            if !paths.dist.contains_key(&v) {
+                paths.dist.insert(v.clone(), next_dist);
+                paths.prev.insert(v.clone(), u.clone());
+                open.push_back(v);
+            }
+        }
+    }
+    paths
+}
+
+/// Dijkstra's algorithm from `start`, finding the shortest distance to everything reachable.
+/// `neighbors` gives the nodes next to a node with how far away they are.
+pub fn dijkstra<N, F, I>(start: N, neighbors: F) -> Paths<N>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    search(start, None, neighbors, |_| 0)
+}
+
+/// A* search from `start` to `goal`.  `heuristic` guesses the distance to the goal from a node,
+/// and must never guess too high.  Returns the distance to the goal and the path there.
+pub fn astar<N, F, I, H>(start: N, goal: &N, neighbors: F, heuristic: H) -> Option<(usize, Vec<N>)>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    H: FnMut(&N) -> usize,
+{
+    let paths = search(start, Some(goal), neighbors, heuristic);
+    Some((paths.distance(goal)?, paths.path_to(goal)?))
+}
+
+/// A node waiting to be looked at, with the cheapest the whole path through it could be.
+struct Open<N> {
+    estimate: usize,
+    dist: usize,
+    node: N,
+}
+
+impl<N> PartialEq for Open<N> {
+    fn eq(&self, other: &Open<N>) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<N> Eq for Open<N> {}
+
+impl<N> PartialOrd for Open<N> {
+    fn partial_cmp(&self, other: &Open<N>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Open<N> {
+    // Reversed, so the BinaryHeap gives us the lowest estimate first.
+    fn cmp(&self, other: &Open<N>) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+/// Dijkstra's algorithm guided by `heuristic`, which is A*.  Stops when it gets to `goal`, if
+/// there is one.
+fn search<N, F, I, H>(start: N, goal: Option<&N>, mut neighbors: F, mut heuristic: H) -> Paths<N>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    H: FnMut(&N) -> usize,
+{
+    let mut paths = Paths::new(start.clone());
+    let mut open = BinaryHeap::new();
+    open.push(Open {
+        estimate: heuristic(&start),
+        dist: 0,
+        node: start,
+    });
+    while let Some(Open { dist, node: u, .. }) = open.pop() {
+        if Some(&u) == goal {
+            break;
+        }
+        if dist > paths.dist[&u] {
+            // We already found a shorter way here.
+            continue;
+        }
+        for (v, cost) in neighbors(&u) {
+            let new_dist = dist + cost;
+            if paths.dist.get(&v).is_none_or(|&d| new_dist < d) {
+                paths.dist.insert(v.clone(), new_dist);
+                paths.prev.insert(v.clone(), u.clone());
+                open.push(Open {
+                    estimate: new_dist + heuristic(&v),
+                    dist: new_dist,
+                    node: v,
+                });
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, Point};
+
+    fn maze() -> Grid<char> {
+        Grid::parse("#######\n#.....#\n#.###.#\n#...#.#\n#######", Ok).unwrap()
+    }
+
+    fn open_neighbors(maze: &Grid<char>, p: &Point) -> Vec<Point> {
+        maze.neighbors4(*p).filter(|&n| maze[n] == '.').collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = maze();
+        let paths = bfs((1, 1), |p| open_neighbors(&maze, p));
+        assert_eq!(Some(6), paths.distance(&(5, 3)));
+        assert_eq!(Some(2), paths.distance(&(1, 3)));
+        assert_eq!(None, paths.distance(&(3, 2)));
+        assert_eq!(
+            Some(vec![(1, 1), (1, 2), (1, 3), (2, 3), (3, 3)]),
+            paths.path_to(&(3, 3))
+        );
+        assert_eq!(Some(vec![(1, 1)]), paths.path_to(&(1, 1)));
+        assert_eq!(11, paths.distances().len());
+    }
+
+    #[test]
+    fn test_dijkstra_and_astar() {
+        // Going down is slow.
+        let maze = maze();
+        let neighbors = |p: &Point| {
+            open_neighbors(&maze, p)
+                .into_iter()
+                .map(|n| (n, if n.1 > p.1 { 5 } else { 1 }))
+                .collect::<Vec<_>>()
+        };
+        let paths = dijkstra((1, 1), neighbors);
+        assert_eq!(Some(14), paths.distance(&(5, 3)));
+        assert_eq!(Some(12), paths.distance(&(3, 3)));
+
+        let manhattan = |p: &Point| ((5 - p.0).abs() + (3 - p.1).abs()) as usize;
+        let (dist, path) = astar((1, 1), &(5, 3), neighbors, manhattan).unwrap();
+        assert_eq!(14, dist);
+        assert_eq!(Some(&(5, 1)), path.get(4));
+        assert_eq!(7, path.len());
+        assert_eq!(None, astar((1, 1), &(3, 2), neighbors, |_| 0));
+    }
+}
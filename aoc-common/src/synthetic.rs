@@ -0,0 +1,50 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Made-up puzzle inputs for benchmarks, so they can be any size and don't need anyone's input.
+
+/// A small xorshift random number generator.  It always gives the same numbers for the same seed,
+/// so the benchmarks measure the same work every time.
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        // Zero would get stuck at zero forever.
+        XorShift(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number from 0 up to but not including `n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Picks one of `choices`.
+    pub fn pick<T: Copy>(&mut self, choices: &[T]) -> T {
+        choices[self.below(choices.len() as u64) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeatable() {
+        let mut a = XorShift::new(18);
+        let mut b = XorShift::new(18);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+            let n = a.below(7);
+            assert_eq!(n, b.below(7));
+            assert!(n < 7);
+        }
+        assert_ne!(0, XorShift::new(0).next_u64());
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Checks the days against the answers we already know are right, so a change to one of them
+//! can't quietly change what it gets.
+//!
+//! The answers live in a TOML file with one `[[answer]]` table per day and part:
+//!
+//!     [[answer]]
+//!     day = 15
+//!     part = 1
+//!     input = "day15/input.txt"
+//!     expected = "27730"
+
+use aoc_common::ParseError;
+use serde::Deserialize;
+use std::{
+    fmt, fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Answer {
+    pub day: u8,
+    pub part: u8,
+    /// Path to the input, relative to the answers file.
+    pub input: String,
+    pub expected: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnswersFile {
+    #[serde(default)]
+    answer: Vec<Answer>,
+}
+
+/// Parses the answers from the text of an answers file.
+pub fn parse_answers(text: &str) -> Result<Vec<Answer>, String> {
+    let file: AnswersFile = toml::from_str(text).map_err(|e| e.to_string())?;
+    for answer in &file.answer {
+        if !(1..=25).contains(&answer.day) || !(1..=2).contains(&answer.part) {
+            return Err(format!(
+                "There is no day {} part {}",
+                answer.day, answer.part
+            ));
+        }
+    }
+    Ok(file.answer)
+}
+
+pub enum Outcome {
+    Pass,
+    Fail(String),
+    Panic,
+    NoInput(String),
+    BadInput(ParseError),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "PASS"),
+            Outcome::Fail(got) => write!(f, "FAIL (got {})", got),
+            Outcome::Panic => write!(f, "FAIL (panicked)"),
+            Outcome::NoInput(e) => write!(f, "FAIL (can't read input: {})", e),
+            Outcome::BadInput(e) => write!(f, "FAIL ({})", e),
+        }
+    }
+}
+
+/// Runs the day and part for one answer, with the input found relative to `base`.
+pub fn check(answer: &Answer, base: &Path) -> (Outcome, Duration) {
+    let input = match fs::read_to_string(base.join(&answer.input)) {
+        Ok(input) => input,
+        Err(e) => return (Outcome::NoInput(e.to_string()), Duration::default()),
+    };
+    let solution = crate::solution_for(answer.day).unwrap();
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        solution
+            .part(answer.part, &input)
+            .map(|answer| answer.to_string())
+    }));
+    let elapsed = start.elapsed();
+    let outcome = match result {
+        Ok(Ok(ref got)) if got == &answer.expected => Outcome::Pass,
+        Ok(Ok(got)) => Outcome::Fail(got),
+        Ok(Err(e)) => Outcome::BadInput(e.in_file(&answer.input)),
+        Err(_) => Outcome::Panic,
+    };
+    (outcome, elapsed)
+}
+
+/// Checks all the answers in the file at `path`, printing a line for each.  Returns true if they
+/// all passed.
+pub fn check_all(path: &str) -> Result<bool, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+    let answers = parse_answers(&text).map_err(|e| format!("{}: {}", path, e))?;
+    let base = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+    let mut results = Vec::new();
+    for answer in &answers {
+        let (outcome, elapsed) = check(answer, base);
+        results.push((answer, outcome, elapsed));
+    }
+
+    println!();
+    let mut failed = 0;
+    for (answer, outcome, elapsed) in &results {
+        if !matches!(outcome, Outcome::Pass) {
+            failed += 1;
+        }
+        println!(
+            "Day {:2} Part {} {:>10.3?} {} (expected {})",
+            answer.day, answer.part, elapsed, outcome, answer.expected
+        );
+    }
+    println!("{} passed, {} failed", results.len() - failed, failed);
+    Ok(failed == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let text = r#"
+[[answer]]
+day = 15
+part = 1
+input = "day15/input.example"
+expected = "27730"
+
+[[answer]]
+day = 20
+part = 1
+input = "day20/input.txt"
+expected = "3930"
+"#;
+        let answers = parse_answers(text).unwrap();
+        assert_eq!(2, answers.len());
+        assert_eq!(
+            Answer {
+                day: 15,
+                part: 1,
+                input: "day15/input.example".to_string(),
+                expected: "27730".to_string(),
+            },
+            answers[0]
+        );
+        assert!(parse_answers("").unwrap().is_empty());
+        assert!(parse_answers("[[answer]]\nday = 15").is_err());
+        let bad_day = "[[answer]]\nday = 26\npart = 1\ninput = \"x\"\nexpected = \"1\"";
+        assert!(parse_answers(bad_day).is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let base = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let mut answer = Answer {
+            day: 15,
+            part: 1,
+            input: "day15/input.example".to_string(),
+            expected: "27730".to_string(),
+        };
+        assert!(matches!(check(&answer, &base).0, Outcome::Pass));
+        answer.expected = "1".to_string();
+        assert!(matches!(check(&answer, &base).0, Outcome::Fail(_)));
+        answer.input = "day20/input.xmpl".to_string();
+        assert!(matches!(check(&answer, &base).0, Outcome::BadInput(_)));
+        answer.input = "day15/no-such-input".to_string();
+        assert!(matches!(check(&answer, &base).0, Outcome::NoInput(_)));
+    }
+}
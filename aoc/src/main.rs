@@ -0,0 +1,329 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Runs any of the days from one binary:
+//!
+//!     aoc run --day 15 --part 2 --input path/to/input.txt
+//!
+//! Leaving out `--day` or `--part` runs all of them.  The input defaults to `dayNN/input.txt`.
+//! With `--format json` each part prints one line of JSON instead, with the answer, how long it
+//! took and any stats the day kept about it:
+//!
+//!     {"answer":"27730","day":15,"elapsed_secs":0.008,"part":1,"stats":{"rounds":47,...}}
+//!
+//! A day that fails prints `{"day":15,"error":"...","part":1}` instead.
+//!
+//!     aoc check [--answers answers.toml]
+//!
+//! checks the days against the answers we already know, see `answers.rs`.
+//!
+//! Either one takes `-q` to keep the days quiet, or `-v` or `-vv` to hear more of what they're up
+//! to, see `aoc_common::logging`.
+
+mod answers;
+
+use aoc_common::{logging, Answer, Solution, Stat};
+use serde_json::{json, Map, Value};
+use std::{
+    env, fs, process,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "Usage: aoc [-q|-v|-vv] run [--day N] [--part 1|2] [--input PATH] \
+[--format text|json]
+       aoc [-q|-v|-vv] check [--answers PATH]";
+
+fn solution_for(day: u8) -> Option<Box<dyn Solution>> {
+    let solution: Box<dyn Solution> = match day {
+        1 => Box::new(day1::Day1),
+        2 => Box::new(day2::Day2),
+        3 => Box::new(day3::Day3),
+        4 => Box::new(day4::Day4),
+        5 => Box::new(day5::Day5),
+        6 => Box::new(day6::Day6),
+        7 => Box::new(day7::Day7),
+        8 => Box::new(day8::Day8),
+        9 => Box::new(day9::Day9),
+        10 => Box::new(day10::Day10),
+        11 => Box::new(day11::Day11),
+        12 => Box::new(day12::Day12),
+        13 => Box::new(day13::Day13),
+        14 => Box::new(day14::Day14),
+        15 => Box::new(day15::Day15),
+        16 => Box::new(day16::Day16),
+        17 => Box::new(day17::Day17),
+        18 => Box::new(day18::Day18),
+        19 => Box::new(day19::Day19),
+        20 => Box::new(day20::Day20),
+        21 => Box::new(day21::Day21),
+        22 => Box::new(day22::Day22),
+        23 => Box::new(day23::Day23),
+        24 => Box::new(day24::Day24),
+        25 => Box::new(day25::Day25),
+        _ => return None,
+    };
+    Some(solution)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct RunArgs {
+    day: Option<u8>,
+    part: Option<u8>,
+    input: Option<String>,
+    format: Format,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run(RunArgs),
+    Check { answers: String },
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        Some("run") => parse_run_args(&args[1..]).map(Command::Run),
+        Some("check") => parse_check_args(&args[1..]),
+        Some(cmd) => Err(format!("Unknown command {}", cmd)),
+        None => Err("No command given".to_string()),
+    }
+}
+
+fn parse_check_args(args: &[String]) -> Result<Command, String> {
+    match args {
+        [] => Ok(Command::Check {
+            answers: "answers.toml".to_string(),
+        }),
+        [flag, path] if flag == "--answers" || flag == "-a" => Ok(Command::Check {
+            answers: path.clone(),
+        }),
+        _ => Err(format!("Unknown options {}", args.join(" "))),
+    }
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut run = RunArgs::default();
+    let mut rest = args.iter();
+    while let Some(flag) = rest.next() {
+        let value = rest
+            .next()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--day" | "-d" => match value.parse() {
+                Ok(day) if (1..=25).contains(&day) => run.day = Some(day),
+                _ => return Err(format!("There is no day {}", value)),
+            },
+            "--part" | "-p" => match value.parse() {
+                Ok(part) if part == 1 || part == 2 => run.part = Some(part),
+                _ => return Err(format!("There is no part {}", value)),
+            },
+            "--input" | "-i" => run.input = Some(value.clone()),
+            "--format" | "-f" => match value.as_str() {
+                "text" => run.format = Format::Text,
+                "json" => run.format = Format::Json,
+                _ => return Err(format!("Unknown format {}", value)),
+            },
+            _ => return Err(format!("Unknown option {}", flag)),
+        }
+    }
+    if run.input.is_some() && run.day.is_none() {
+        return Err("--input only makes sense with --day".to_string());
+    }
+    Ok(run)
+}
+
+/// One part's answer as a JSON object.
+fn answer_json(day: u8, part: u8, answer: &Answer, elapsed: Duration) -> Value {
+    let stats: Map<String, Value> = answer
+        .stats()
+        .iter()
+        .map(|(name, stat)| {
+            let value = match stat {
+                Stat::Number(n) => json!(n),
+                Stat::Text(s) => json!(s),
+            };
+            (name.to_string(), value)
+        })
+        .collect();
+    json!({
+        "day": day,
+        "part": part,
+        "answer": answer.to_string(),
+        "elapsed_secs": elapsed.as_secs_f64(),
+        "stats": stats,
+    })
+}
+
+/// Says that a day failed, to stderr for people or as JSON on stdout for everything else.
+fn report_error(format: Format, day: u8, part: Option<u8>, error: &str) {
+    match format {
+        Format::Text => eprintln!("Day {}: {}", day, error),
+        Format::Json => println!("{}", json!({ "day": day, "part": part, "error": error })),
+    }
+}
+
+/// Runs the parts asked for on one day.  Returns false if the input couldn't be read or parsed.
+fn run_day(day: u8, parts: &[u8], input_path: &str, format: Format) -> bool {
+    let input = match fs::read_to_string(input_path) {
+        Ok(input) => input,
+        Err(e) => {
+            let error = format!("can't read {}: {}", input_path, e);
+            report_error(format, day, None, &error);
+            return false;
+        }
+    };
+    let solution = solution_for(day).unwrap();
+    for &part in parts {
+        let start = Instant::now();
+        let answer = match solution.part(part, &input) {
+            Ok(answer) => answer,
+            Err(e) => {
+                let error = e.in_file(input_path).to_string();
+                report_error(format, day, Some(part), &error);
+                return false;
+            }
+        };
+        let elapsed = start.elapsed();
+        match format {
+            Format::Text => {
+                print!("Day {} Part {}: {} ({:?})", day, part, answer, elapsed);
+                for (name, stat) in answer.stats() {
+                    print!(" {}={}", name, stat);
+                }
+                println!();
+            }
+            Format::Json => println!("{}", answer_json(day, part, &answer, elapsed)),
+        }
+    }
+    true
+}
+
+fn run(run: RunArgs) -> bool {
+    let days: Vec<u8> = match run.day {
+        Some(day) => vec![day],
+        None => (1..=25).collect(),
+    };
+    let parts: Vec<u8> = match run.part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+
+    let mut all_ok = true;
+    for day in days {
+        let input_path = run
+            .input
+            .clone()
+            .unwrap_or_else(|| format!("day{:02}/input.txt", day));
+        all_ok &= run_day(day, &parts, &input_path, run.format);
+    }
+    all_ok
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (level, args) = logging::take_verbosity(&args);
+    logging::init(level);
+    let all_ok = match parse_args(&args) {
+        Ok(Command::Run(args)) => run(args),
+        Ok(Command::Check { answers }) => match answers::check_all(&answers) {
+            Ok(all_ok) => all_ok,
+            Err(msg) => {
+                eprintln!("{}", msg);
+                false
+            }
+        },
+        Err(msg) => {
+            eprintln!("{}\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+    if !all_ok {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            Ok(Command::Run(RunArgs::default())),
+            parse_args(&args("run"))
+        );
+        let expected = RunArgs {
+            day: Some(15),
+            part: Some(2),
+            input: Some("in.txt".to_string()),
+            format: Format::Text,
+        };
+        assert_eq!(
+            Ok(Command::Run(expected)),
+            parse_args(&args("run --day 15 --part 2 --input in.txt"))
+        );
+        assert_eq!(
+            Ok(Command::Run(RunArgs {
+                format: Format::Json,
+                ..RunArgs::default()
+            })),
+            parse_args(&args("run --format json"))
+        );
+        assert!(parse_args(&args("run --format yaml")).is_err());
+        assert!(parse_args(&args("")).is_err());
+        assert!(parse_args(&args("walk")).is_err());
+        assert!(parse_args(&args("run --day 26")).is_err());
+        assert!(parse_args(&args("run --part 3")).is_err());
+        assert!(parse_args(&args("run --day")).is_err());
+        assert!(parse_args(&args("run --input in.txt")).is_err());
+        assert_eq!(
+            Ok(Command::Check {
+                answers: "answers.toml".to_string()
+            }),
+            parse_args(&args("check"))
+        );
+        assert_eq!(
+            Ok(Command::Check {
+                answers: "mine.toml".to_string()
+            }),
+            parse_args(&args("check --answers mine.toml"))
+        );
+        assert!(parse_args(&args("check --day 1")).is_err());
+    }
+
+    #[test]
+    fn test_answer_json() {
+        let answer = Answer::new(27730)
+            .with_stat("rounds", 47)
+            .with_stat("winner", 'G');
+        let value = answer_json(15, 1, &answer, Duration::from_millis(1500));
+        assert_eq!(
+            json!({
+                "day": 15,
+                "part": 1,
+                "answer": "27730",
+                "elapsed_secs": 1.5,
+                "stats": { "rounds": 47, "winner": "G" },
+            }),
+            value
+        );
+    }
+
+    #[test]
+    fn test_every_day_has_a_solution() {
+        for day in 1..=25 {
+            assert!(solution_for(day).is_some());
+        }
+        assert!(solution_for(26).is_none());
+    }
+}
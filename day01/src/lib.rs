@@ -0,0 +1,69 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_number, Answer, ParseError, Solution};
+use std::collections::HashSet;
+
+pub struct Day1;
+
+fn parse_pattern(input: &str) -> Result<Vec<i32>, ParseError> {
+    let pattern: Vec<i32> = numbered_lines(input)
+        .map(|(line_no, l)| parse_number(line_no, l, l))
+        .collect::<Result<_, _>>()?;
+    if pattern.is_empty() {
+        return Err(ParseError::line(1, "", "there are no frequency changes"));
+    }
+    Ok(pattern)
+}
+
+/// Returns the first frequency reached twice, and how many steps it took to get there, or None if
+/// no frequency ever is.
+fn first_repeated_frequency(pattern: &[i32]) -> Option<(i32, usize)> {
+    // Each time round the pattern every frequency moves on by the total.  So if the frequencies
+    // in the first time round are all different modulo the total, none of them come round again.
+    let total: i32 = pattern.iter().sum();
+    if total != 0 {
+        let mut residues = HashSet::new();
+        let firsts = pattern.iter().scan(0, |a, &x| {
+            let before = *a;
+            *a += x;
+            Some(before)
+        });
+        if firsts
+            .map(|f| f.rem_euclid(total))
+            .all(|r| residues.insert(r))
+        {
+            return None;
+        }
+    }
+
+    let sums = pattern.iter().cycle().scan(0, |a, &x| {
+        *a += x;
+        Some(*a)
+    });
+
+    let mut reached = HashSet::new();
+    reached.insert(0);
+
+    for (steps, current_frequency) in sums.enumerate() {
+        if reached.contains(&current_frequency) {
+            return Some((current_frequency, steps));
+        }
+        reached.insert(current_frequency);
+    }
+    None
+}
+
+impl Solution for Day1 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let pattern = parse_pattern(input)?;
+        Ok(Answer::new(pattern.iter().sum::<i32>()).with_stat("changes", pattern.len()))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let pattern = parse_pattern(input)?;
+        let (current_frequency, steps) = first_repeated_frequency(&pattern)
+            .ok_or_else(|| ParseError::fault("no frequency is reached twice"))?;
+        Ok(Answer::new(current_frequency).with_stat("steps", steps))
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, Answer, ParseError, Solution};
+use log::{debug, info};
+use std::collections::HashMap;
+
+/// If there's one letter which is different between `one` and `two`, then
+/// returns Some(index).  Otherwise None.
+fn edit_distance_replace_one(one: &str, two: &str) -> Option<usize> {
+    if one.len() != two.len() {
+        return None;
+    }
+    let one_chars = one.chars();
+    let two_chars = two.chars();
+    let mut together = one_chars.zip(two_chars);
+    let idx = together.position(|(x, y)| x != y)?;
+    if one[idx + 1..] != two[idx + 1..] {
+        None
+    } else {
+        Some(idx)
+    }
+}
+
+/// Returns whether the box contains exactly two of the same letter, or exactly three of the same
+/// letter.
+fn letter_counts(box_id: &str) -> (bool, bool) {
+    let mut letters = HashMap::<char, i32>::new();
+    for c in box_id.chars() {
+        let letter_count = letters.entry(c).or_insert(0);
+        *letter_count += 1;
+    }
+    let two = letters.values().any(|count| *count == 2);
+    let three = letters.values().any(|count| *count == 3);
+    (two, three)
+}
+
+/// Returns the checksum of the box IDs: the number of IDs with exactly two of any letter
+/// multiplied by the number with exactly three of any letter.
+fn checksum(box_ids: &[&str]) -> i32 {
+    let mut twice_count = 0;
+    let mut thrice_count = 0;
+    for box_id in box_ids.iter() {
+        let (twice, thrice) = letter_counts(box_id);
+        if twice {
+            twice_count += 1;
+        }
+        if thrice {
+            thrice_count += 1;
+        }
+    }
+    debug!("Box IDs with two of any letter: {}", twice_count);
+    debug!("Box IDs with three of any letter: {}", thrice_count);
+    twice_count * thrice_count
+}
+
+/// Finds the two box IDs which differ by only one letter, and returns the letters they share.
+fn common_letters(box_ids: &[&str]) -> Option<String> {
+    let mut iter = box_ids.iter().peekable();
+
+    while iter.peek().is_some() {
+        let this_id = iter.next().unwrap();
+        // iter now contains every id that we haven't checked this_id against
+        let rest = iter.clone();
+        for id in rest {
+            if let Some(idx) = edit_distance_replace_one(this_id, id) {
+                info!(
+                    "{} and {} only differ in the {}th letter.",
+                    this_id, id, idx
+                );
+                let (before, after) = this_id.split_at(idx);
+                let (_, after) = after.split_at(1);
+                return Some(format!("{}{}", before, after));
+            }
+        }
+    }
+    None
+}
+
+/// Box IDs are made of lowercase letters.
+fn parse_box_ids(input: &str) -> Result<Vec<&str>, ParseError> {
+    let box_ids: Vec<&str> = numbered_lines(input)
+        .map(
+            |(line_no, line)| match line.find(|c: char| !c.is_ascii_lowercase()) {
+                Some(idx) => Err(ParseError::at(
+                    line_no,
+                    line,
+                    &line[idx..idx + 1],
+                    "box IDs are only lowercase letters",
+                )),
+                None => Ok(line),
+            },
+        )
+        .collect::<Result<_, _>>()?;
+    if box_ids.is_empty() {
+        return Err(ParseError::line(1, "", "there are no box IDs"));
+    }
+    Ok(box_ids)
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let box_ids = parse_box_ids(input)?;
+        info!("{} box IDs", box_ids.len());
+        Ok(Answer::new(checksum(&box_ids)))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let box_ids = parse_box_ids(input)?;
+        let common = common_letters(&box_ids)
+            .ok_or_else(|| ParseError::fault("no two box IDs differ by one letter"))?;
+        Ok(Answer::new(common))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        assert_eq!((false, false), letter_counts("abcdef"));
+        assert_eq!((true, true), letter_counts("bababc"));
+        assert_eq!((true, false), letter_counts("abbcde"));
+        assert_eq!((false, true), letter_counts("abcccd"));
+        assert_eq!((true, false), letter_counts("aabcdd"));
+        assert_eq!((true, false), letter_counts("abcdee"));
+        assert_eq!((false, true), letter_counts("ababab"));
+    }
+
+    #[test]
+    fn test_replace_one() {
+        assert_eq!(None, edit_distance_replace_one("abcde", "axcye"));
+        assert_eq!(Some(2), edit_distance_replace_one("fghij", "fguij"));
+        assert_eq!(None, edit_distance_replace_one("fghij", "fghij"));
+    }
+    #[test]
+    fn test_common_letters() {
+        let ids = [
+            "abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz",
+        ];
+        assert_eq!(Some("fgij".to_string()), common_letters(&ids));
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, Answer, ParseError, Solution};
+use log::{debug, info};
+use std::collections::{HashMap, HashSet};
+
+type Place = (i32, i32);
+
+#[derive(PartialEq, Debug)]
+struct Claim {
+    id: i32,
+    start: (i32, i32),
+    width: i32,
+    height: i32,
+}
+
+// A Claim Map maps places on the fabric to claims that want to use that piece
+type ClaimMap = HashMap<Place, HashSet<i32>>;
+
+impl Claim {
+    /// Parses a claim like "#123 @ 3,2: 5x4" from line `line_no`.
+    fn parse(line_no: usize, s: &str) -> Result<Claim, ParseError> {
+        let numbers: Vec<i32> = parse_numbers(line_no, s, 5)?;
+        Ok(Claim {
+            id: numbers[0],
+            start: (numbers[1], numbers[2]),
+            width: numbers[3],
+            height: numbers[4],
+        })
+    }
+
+    fn places<'a>(&'a self) -> impl Iterator<Item = Place> + 'a {
+        let xs = self.start.0..self.start.0 + self.width;
+        let ys = self.start.1..self.start.1 + self.height;
+        let width: usize = self.width as usize;
+        let ys_repeated = ys.flat_map(move |y| std::iter::repeat_n(y, width));
+        xs.cycle().zip(ys_repeated)
+    }
+
+    fn fill(&self, claims: &mut ClaimMap) {
+        for place in self.places() {
+            let entry = claims.entry(place).or_default();
+            entry.insert(self.id);
+        }
+    }
+}
+
+/// Places all the claims on the fabric.  Returns the number of overallocated spaces, and the
+/// claims which don't overlap with any other claim.
+fn allocate(claims: &[Claim]) -> (usize, HashSet<i32>) {
+    let mut map = ClaimMap::new();
+
+    for claim in claims {
+        claim.fill(&mut map);
+    }
+
+    let mut overallocated = 0;
+    let mut clear_claims: HashSet<i32> = claims.iter().map(|x| x.id).collect();
+    for (key, val) in map.iter() {
+        if val.len() > 1 {
+            debug!("{:?} is overallocated: {} claims", key, val.len());
+            for id in val {
+                clear_claims.remove(id);
+            }
+            overallocated += 1;
+        }
+    }
+    (overallocated, clear_claims)
+}
+
+fn parse_claims(input: &str) -> Result<Vec<Claim>, ParseError> {
+    let claims = numbered_lines(input)
+        .map(|(line_no, line)| Claim::parse(line_no, line))
+        .collect::<Result<Vec<Claim>, ParseError>>()?;
+    if claims.is_empty() {
+        return Err(ParseError::line(1, "", "there are no claims"));
+    }
+    info!("{} claims", claims.len());
+    Ok(claims)
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (overallocated, _) = allocate(&parse_claims(input)?);
+        Ok(Answer::new(overallocated))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (_, clear_claims) = allocate(&parse_claims(input)?);
+        info!(
+            "Claims {:?} are not overlapping with anything",
+            clear_claims
+        );
+        let clear = clear_claims
+            .iter()
+            .next()
+            .ok_or_else(|| ParseError::fault("every claim overlaps another"))?;
+        Ok(Answer::new(*clear))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_claims() {
+        let claim = Claim {
+            id: 1,
+            start: (1, 3),
+            width: 4,
+            height: 4,
+        };
+        assert_eq!(Ok(claim), Claim::parse(1, "#1 @ 1,3: 4x4"));
+        let claim = Claim {
+            id: 2,
+            start: (3, 1),
+            width: 4,
+            height: 4,
+        };
+        assert_eq!(Ok(claim), Claim::parse(1, "#2 @ 3,1: 4x4"));
+        let claim = Claim {
+            id: 3,
+            start: (5, 5),
+            width: 2,
+            height: 2,
+        };
+        assert_eq!(Ok(claim), Claim::parse(1, "#3 @ 5,5: 2x2"));
+        let claim = Claim {
+            id: 123,
+            start: (3, 2),
+            width: 5,
+            height: 4,
+        };
+        assert_eq!(Ok(claim), Claim::parse(1, "#123 @ 3,2: 5x4"));
+        assert!(Claim::parse(4, "#123 @ 3,2: 5").is_err());
+    }
+
+    #[test]
+    fn fill_places() {
+        let mut map = ClaimMap::new();
+        Claim::parse(1, "#1 @ 1,3: 4x4").unwrap().fill(&mut map);
+        Claim::parse(1, "#2 @ 3,1: 4x4").unwrap().fill(&mut map);
+        Claim::parse(1, "#3 @ 5,5: 2x2").unwrap().fill(&mut map);
+
+        assert_eq!(2, map.get(&(3, 3)).unwrap().len());
+        assert_eq!(2, map.get(&(3, 4)).unwrap().len());
+        assert_eq!(2, map.get(&(4, 3)).unwrap().len());
+        assert_eq!(2, map.get(&(4, 4)).unwrap().len());
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, Answer, ParseError, Solution};
+use log::{debug, info};
+use std::collections::HashMap;
+
+struct GuardRecord {
+    id: u32,
+    minute_count: [u32; 60],
+}
+
+impl GuardRecord {
+    /// Makes a new empty guard record with the given id
+    fn new(id: u32) -> GuardRecord {
+        GuardRecord {
+            id,
+            minute_count: [0; 60],
+        }
+    }
+
+    /// Add a record of a guard's shift.
+    fn add_shift(&mut self, fallwake: &[u32]) {
+        for (sleep, wake) in fallwake.to_vec().chunks(2).map(|x| (x[0], x[1])) {
+            for min in sleep as usize..wake as usize {
+                self.minute_count[min] += 1;
+            }
+        }
+    }
+
+    fn sum_asleep(&self) -> u32 {
+        self.minute_count.iter().sum()
+    }
+
+    fn sleepiest_minute(&self) -> usize {
+        self.minute_count
+            .iter()
+            .enumerate()
+            .max_by(|(_, x), (_, y)| x.cmp(y))
+            .unwrap()
+            .0
+    }
+}
+
+/// Reads the guard records from the input.  The input doesn't need to be sorted.
+fn parse_records(input: &str) -> Result<HashMap<u32, GuardRecord>, ParseError> {
+    let mut guard_records: HashMap<u32, GuardRecord> = HashMap::new();
+
+    // The timestamps sort in chronological order.
+    let mut sorted: Vec<(usize, &str)> = numbered_lines(input).collect();
+    sorted.sort_unstable_by_key(|(_, line)| *line);
+
+    let mut lines = sorted.into_iter().peekable();
+    let mut shifts = 0;
+    while let Some((line_no, shift_begins)) = lines.next() {
+        if !shift_begins.contains("begins shift") {
+            return Err(ParseError::line(
+                line_no,
+                shift_begins,
+                "expected a guard to begin their shift first",
+            ));
+        }
+        let numbers = parse_numbers(line_no, shift_begins, 6)?;
+        let guard_id = numbers[5];
+        let record = guard_records
+            .entry(guard_id)
+            .or_insert(GuardRecord::new(guard_id));
+        let mut fallwake = Vec::new();
+        while let Some((line_no, line)) = lines.peek().cloned() {
+            if line.contains("Guard") {
+                break;
+            }
+            lines.next();
+            let expected = if fallwake.len() % 2 == 0 {
+                "falls asleep"
+            } else {
+                "wakes up"
+            };
+            if !line.ends_with(expected) {
+                let message = format!("expected the guard {} next", expected);
+                return Err(ParseError::line(line_no, line, &message));
+            }
+            let numbers: Vec<u32> = parse_numbers(line_no, line, 5)?;
+            let minutes = numbers[4];
+            if minutes >= 60 {
+                return Err(ParseError::line(
+                    line_no,
+                    line,
+                    "guards only sleep during the midnight hour",
+                ));
+            }
+            fallwake.push(minutes);
+        }
+        if fallwake.len() % 2 == 1 {
+            return Err(ParseError::line(
+                line_no,
+                shift_begins,
+                "this guard never wakes up",
+            ));
+        }
+        record.add_shift(&fallwake);
+        shifts += 1;
+    }
+
+    if guard_records.is_empty() {
+        return Err(ParseError::line(1, "", "there are no shifts"));
+    }
+    info!("{} guards with {} shifts", guard_records.len(), shifts);
+    Ok(guard_records)
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let guard_records = parse_records(input)?;
+
+        // Find the guard with the most minutes asleep
+        let sleepiest = guard_records
+            .values()
+            .max_by(|x, y| x.sum_asleep().cmp(&y.sum_asleep()))
+            .ok_or_else(|| ParseError::line(1, "", "there are no shifts"))?;
+
+        let sleepy_minute = sleepiest.sleepiest_minute();
+        Ok(Answer::new(sleepiest.id * sleepy_minute as u32)
+            .with_stat("guard", sleepiest.id)
+            .with_stat("minutes_asleep", sleepiest.sum_asleep())
+            .with_stat("minute", sleepy_minute))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let guard_records = parse_records(input)?;
+
+        let mut max_guard_id: u32 = 0;
+        let mut max_guard_minute: u32 = 0;
+        let mut max_guard_sleeps: u32 = 0;
+
+        for guard in guard_records.values() {
+            let sleepy_minute = guard.sleepiest_minute();
+            let sleeps = guard.minute_count[sleepy_minute];
+            debug!(
+                "Sleepiest minute for guard #{} is {} past (asleep {} times)",
+                guard.id, sleepy_minute, sleeps
+            );
+            if sleeps > max_guard_sleeps {
+                max_guard_id = guard.id;
+                max_guard_minute = sleepy_minute as u32;
+                max_guard_sleeps = sleeps;
+            }
+        }
+
+        Ok(Answer::new(max_guard_id * max_guard_minute)
+            .with_stat("guard", max_guard_id)
+            .with_stat("minute", max_guard_minute)
+            .with_stat("times_asleep", max_guard_sleeps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up
+";
+
+    #[test]
+    fn test_example() {
+        assert_eq!("240", Day4.part1(EXAMPLE).unwrap().to_string());
+        assert_eq!("4455", Day4.part2(EXAMPLE).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_bad_records() {
+        let err = parse_records(&EXAMPLE.replace("00:25] wakes up", "00:25] falls asleep"))
+            .err()
+            .unwrap();
+        assert_eq!(3, err.line);
+        let err = parse_records("[1518-11-01 00:05] falls asleep\n")
+            .err()
+            .unwrap();
+        assert_eq!(1, err.line);
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::synthetic::XorShift;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use day5::react;
+
+/// A polymer of `len` random units.  There are only a few kinds of unit so plenty of them react.
+fn polymer(len: usize) -> String {
+    let mut rng = XorShift::new(5);
+    (0..len).map(|_| rng.pick(b"aAbBcCdD") as char).collect()
+}
+
+fn bench_react(c: &mut Criterion) {
+    let mut group = c.benchmark_group("react");
+    for &len in &[1_000, 5_000, 20_000] {
+        let chars = polymer(len);
+        group.bench_with_input(BenchmarkId::new("all", len), &chars, |b, chars| {
+            b.iter_batched(
+                || chars.clone(),
+                |mut chars| react(&mut chars, None),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("without_a", len), &chars, |b, chars| {
+            b.iter_batched(
+                || chars.clone(),
+                |mut chars| react(&mut chars, Some(b'a')),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_react);
+criterion_main!(benches);
@@ -0,0 +1,137 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{Answer, ParseError, Solution};
+use log::{debug, info};
+
+fn pair(a: u8, b: u8) -> bool {
+    if a < 97 {
+        a + 32 == b
+    } else {
+        a - 32 == b
+    }
+}
+
+fn caseless_match(a: u8, b: u8) -> bool {
+    match (a < 97, b < 97) {
+        (true, true) => a == b,
+        (false, true) => a == b + 32,
+        (true, false) => a + 32 == b,
+        (false, false) => a == b,
+    }
+}
+
+/// Reacts the polymer in `chars` until nothing else reacts, leaving out the unit `without` (in
+/// either case) if it's given.
+pub fn react(chars: &mut String, without: Option<u8>) {
+    let mut i = 0;
+    while i != chars.len() - 1 {
+        let x = chars.as_bytes()[i];
+        if without.is_some() && caseless_match(x, without.unwrap()) {
+            chars.remove(i);
+            i = i.saturating_sub(1);
+            continue;
+        }
+        let y = chars.as_bytes()[i + 1];
+        if pair(x, y) {
+            chars.replace_range(i..i + 2, "");
+            if chars.is_empty() {
+                return;
+            }
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    let x = chars.as_bytes()[i];
+    if without.is_some() && caseless_match(x, without.unwrap()) {
+        chars.remove(i);
+    }
+}
+
+/// The polymer is one line of letters.
+fn parse_polymer(input: &str) -> Result<String, ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    if line.is_empty() {
+        return Err(ParseError::line(1, line, "there's no polymer"));
+    }
+    if let Some(idx) = line.find(|c: char| !c.is_ascii_alphabetic()) {
+        let bad = &line[idx..idx + 1];
+        return Err(ParseError::at(1, line, bad, "polymers are made of letters"));
+    }
+    Ok(line.to_string())
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let mut polymer = parse_polymer(input)?;
+        react(&mut polymer, None);
+        Ok(Answer::new(polymer.len()))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let chars = parse_polymer(input)?;
+        let mut original = chars.clone();
+
+        react(&mut original, None);
+        info!("Reacting original: final length {}", original.len());
+
+        let mut smallest_char = 'a';
+        let mut smallest_len = chars.len();
+
+        for c in b'a'..b'{' {
+            let mut new = original.clone();
+            react(&mut new, Some(c));
+            debug!("Reacted w/o {}: length {}", c as char, new.len());
+            if new.len() < smallest_len {
+                smallest_char = c as char;
+                smallest_len = new.len();
+            }
+        }
+
+        Ok(Answer::new(smallest_len).with_stat("removed", smallest_char))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_react() {
+        let mut a = "AcCb".to_string();
+        let a2 = "Ab";
+        react(&mut a, None);
+        assert_eq!(a2, a);
+        let mut c = "dabAcCaCBAcCcaDA".to_string();
+        let c4 = "dabCBAcaDA";
+        react(&mut c, None);
+        assert_eq!(c4, c);
+        react(&mut c, None);
+        assert_eq!(c4, c);
+    }
+
+    #[test]
+    fn test_without() {
+        let a = "dabAcCaCBAcCcaDA".to_string();
+        let mut b = a.clone();
+        react(&mut b, Some(b'a'));
+        let a2 = "dbCBcD";
+        assert_eq!(a2, b);
+        let mut b = a.clone();
+        react(&mut b, Some(b'b'));
+        let a3 = "daCAcaDA";
+        assert_eq!(a3, b);
+        let mut b = a.clone();
+        react(&mut b, Some(b'c'));
+        let a4 = "daDA";
+        assert_eq!(a4, b);
+        let mut b = a.clone();
+        react(&mut b, Some(b'd'));
+        let a5 = "abCBAc";
+        assert_eq!(a5, b);
+    }
+
+}
@@ -0,0 +1,126 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, Answer, ParseError, Solution};
+use log::info;
+use std::{cmp::max, collections::HashMap};
+
+struct Point {
+    id: char,
+    x: i32,
+    y: i32,
+}
+
+fn manhattan(a: &Point, b: &Point) -> i32 {
+    (a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+fn id_to_char(id: i32) -> char {
+    if id < 26 {
+        (id + 97) as u8 as char
+    } else {
+        (id - 26 + 65) as u8 as char
+    }
+}
+
+fn parse_points(input: &str) -> Result<Vec<Point>, ParseError> {
+    let points: Vec<Point> = numbered_lines(input)
+        .map(|(line_no, pt_txt)| {
+            let nums = parse_numbers(line_no, pt_txt, 2)?;
+            Ok(Point {
+                id: id_to_char(line_no as i32 - 1),
+                x: nums[0],
+                y: nums[1],
+            })
+        })
+        .collect::<Result<_, ParseError>>()?;
+    if points.is_empty() {
+        return Err(ParseError::line(1, "", "there are no coordinates"));
+    }
+    Ok(points)
+}
+
+/// Surveys the area around the points.  Returns the size of the largest area that isn't infinite,
+/// and the number of locations with a total distance to all points less than `safe_sum_dist`.
+fn survey(points: &[Point], safe_sum_dist: i32) -> (usize, usize) {
+    let largest_side = points.iter().map(|p| max(p.x, p.y)).max().unwrap();
+
+    // For each point id, the list of points that are closest to that point.
+    let mut closest_points: HashMap<char, Vec<Point>> = HashMap::new();
+
+    let board_min = 0;
+    let board_max = largest_side + 1;
+
+    let mut safe_points = 0;
+
+    for board_y in board_min..board_max + 1 {
+        for board_x in board_min..board_max + 1 {
+            let board_pt = Point {
+                id: '.',
+                x: board_x,
+                y: board_y,
+            };
+            let mut closest_id = None;
+            let mut closest_dist = 2 * (board_max - board_min);
+            let mut sum_dist = 0;
+            for point in points {
+                let dist = manhattan(&board_pt, point);
+                sum_dist += dist;
+                if dist < closest_dist {
+                    closest_id = Some(point.id);
+                    closest_dist = dist;
+                } else if closest_id.is_some() && dist == closest_dist {
+                    closest_id = None;
+                    closest_dist = dist;
+                }
+            }
+            if sum_dist < safe_sum_dist {
+                safe_points += 1;
+            }
+            if let Some(id) = closest_id {
+                let entry = closest_points.entry(id).or_default();
+                entry.push(board_pt);
+            }
+        }
+    }
+
+    let mut largest_area_not_inf_id = None;
+    let mut largest_area_not_inf_points = 0;
+    'outer: for (id, points) in closest_points {
+        for point in &points {
+            // If it touches an edge then it is infinite and doesn't count.
+            if point.x == board_min
+                || point.y == board_min
+                || point.x == board_max
+                || point.y == board_max
+            {
+                continue 'outer;
+            }
+        }
+        if points.len() > largest_area_not_inf_points {
+            largest_area_not_inf_id = Some(id);
+            largest_area_not_inf_points = points.len();
+        }
+    }
+
+    info!(
+        "The largest space not infinite is id {} with {} spaces",
+        largest_area_not_inf_id.unwrap(),
+        largest_area_not_inf_points
+    );
+    (largest_area_not_inf_points, safe_points)
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (largest_area, _) = survey(&parse_points(input)?, 10000);
+        Ok(Answer::new(largest_area))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (_, safe_points) = survey(&parse_points(input)?, 10000);
+        Ok(Answer::new(safe_points))
+    }
+}
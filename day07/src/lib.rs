@@ -0,0 +1,221 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, Answer, ParseError, Solution};
+use log::{debug, info, trace};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone)]
+struct Dag {
+    edges: HashMap<char, HashSet<char>>,
+}
+
+impl Dag {
+    fn new() -> Dag {
+        Dag {
+            edges: HashMap::new(),
+        }
+    }
+
+    // Adds an edge from `parent` to `child`, adding new nodes
+    // to the graph if they don't exist yet.
+    // TODO: make this panic if it introduces a loop
+    fn add_edge(&mut self, parent: char, child: char) {
+        // Add the child to the set so that we can find it later.
+        self.edges.entry(child).or_default();
+        let childs = self.edges.entry(parent).or_default();
+        childs.insert(child);
+    }
+
+    // Find a node that doesn't have any parents.
+    fn find_roots(&self) -> HashSet<char> {
+        let mut possible_roots: HashSet<char> = self.edges.keys().copied().collect();
+        for (_, childs) in self.edges.iter() {
+            for child in childs {
+                possible_roots.remove(child);
+            }
+        }
+        possible_roots
+    }
+
+    fn remove_root(&mut self, node: &char) -> bool {
+        self.edges.remove(node).is_some()
+    }
+
+    fn len(&self) -> usize {
+        self.edges.len()
+    }
+}
+
+struct WorkTimer {
+    task: char,
+    time_left: u8,
+}
+
+impl WorkTimer {
+    fn new(task: char) -> WorkTimer {
+        let time_left = 60 + (task as u8 - b'A' + 1);
+        WorkTimer { task, time_left }
+    }
+
+    fn done(&self) -> bool {
+        self.time_left == 0
+    }
+
+    fn work(&mut self) -> u8 {
+        if self.time_left > 0 {
+            self.time_left -= 1;
+        }
+        self.time_left
+    }
+}
+
+struct Workers {
+    tasks: Vec<WorkTimer>,
+}
+
+impl Workers {
+    fn new() -> Workers {
+        Workers { tasks: Vec::new() }
+    }
+
+    fn are_working(&self) -> HashSet<char> {
+        self.tasks.iter().map(|x| x.task).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    fn add_task(&mut self, task: char) {
+        self.tasks.push(WorkTimer::new(task));
+    }
+
+    // Returns the set of tasks that are complete.
+    // Those tasks are removed from the set of workers.
+    fn work(&mut self) -> HashSet<char> {
+        for x in self.tasks.iter_mut() {
+            x.work();
+        }
+        let done: HashSet<char> = self
+            .tasks
+            .iter()
+            .filter(|x| x.done())
+            .map(|x| x.task)
+            .collect();
+        self.tasks.retain(|x| !x.done());
+        done
+    }
+}
+
+fn order_with_workers(count: usize, mut steps: Dag) -> (String, usize) {
+    let mut ikea_order = String::new();
+
+    let mut second = 0;
+
+    let mut workers = Workers::new();
+
+    while steps.len() > 0 {
+        let done = workers.work();
+        for task in done {
+            steps.remove_root(&task);
+            ikea_order.push(task);
+        }
+        let mut sorted: Vec<char>;
+        {
+            let ready = steps.find_roots();
+            let working = workers.are_working();
+            let ready_available = ready.difference(&working);
+            sorted = ready_available.copied().collect();
+        }
+        sorted.as_mut_slice().sort_unstable();
+        for task in sorted.iter() {
+            if workers.len() >= count {
+                break;
+            }
+            workers.add_task(*task);
+        }
+        debug!("{}\t{:?}\t{}", second, workers.are_working(), ikea_order);
+        second += 1;
+    }
+    info!(
+        "With {} workers, finished in {} steps in order: {}",
+        count,
+        second - 1,
+        ikea_order
+    );
+    (ikea_order, second - 1)
+}
+
+/// Parses the name of a step, which is one capital letter.
+fn parse_step(line_no: usize, line: &str, word: &str) -> Result<char, ParseError> {
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_uppercase() => Ok(c),
+        _ => Err(ParseError::at(
+            line_no,
+            line,
+            word,
+            "steps are one capital letter",
+        )),
+    }
+}
+
+fn parse_steps(input: &str) -> Result<Dag, ParseError> {
+    let mut steps = Dag::new();
+
+    for (line_no, line) in numbered_lines(input) {
+        let words: Vec<&str> = line.split(' ').collect();
+        if words.len() != 10 || words[0] != "Step" || words[6] != "step" {
+            return Err(ParseError::line(
+                line_no,
+                line,
+                "expected \"Step X must be finished before step Y can begin.\"",
+            ));
+        }
+        let before = parse_step(line_no, line, words[1])?;
+        let after = parse_step(line_no, line, words[7])?;
+        trace!("Step {} before {}", before, after);
+        steps.add_edge(before, after);
+    }
+    Ok(steps)
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (order, _) = order_with_workers(1, parse_steps(input)?);
+        Ok(Answer::new(order))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (_, seconds) = order_with_workers(5, parse_steps(input)?);
+        Ok(Answer::new(seconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_steps() {
+        let input = "Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.
+";
+        let (order, _) = order_with_workers(1, parse_steps(input).unwrap());
+        assert_eq!("CABDFE", order);
+
+        let err = parse_steps("Step C must be finished before step a can begin.")
+            .err()
+            .unwrap();
+        assert_eq!((1, 37), (err.line, err.column));
+        assert!(parse_steps("Step C before A").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{parse_number, Answer, ParseError, Solution};
+
+struct MetadataTree {
+    children: Vec<MetadataTree>,
+    metadata: Vec<u32>,
+}
+
+impl MetadataTree {
+    /// Builds a tree out of the numbers.  Returns None if they run out before the tree is done.
+    fn parse(nums: &mut dyn Iterator<Item = u32>) -> Option<MetadataTree> {
+        let mut children: Vec<MetadataTree> = Vec::new();
+        let num_children = nums.next()?;
+        let num_metadata = nums.next()?;
+        for _i in 0..num_children {
+            children.push(MetadataTree::parse(nums)?);
+        }
+        let metadata: Vec<u32> = nums.take(num_metadata as usize).collect();
+        if metadata.len() != num_metadata as usize {
+            return None;
+        }
+        Some(MetadataTree { children, metadata })
+    }
+
+    fn all_metadata(&self) -> Vec<u32> {
+        let mut all = Vec::new();
+        for c in self.children.iter() {
+            all.extend_from_slice(&c.all_metadata());
+        }
+        all.extend_from_slice(self.metadata.as_slice());
+        all
+    }
+
+    fn value(&self) -> u32 {
+        if self.children.is_empty() {
+            return self.metadata.iter().sum();
+        }
+        let mut val = 0;
+        for idx in self.metadata.iter() {
+            if idx != &0 {
+                if let Some(child) = self.children.get((idx - 1) as usize) {
+                    val += child.value();
+                }
+            }
+        }
+        val
+    }
+}
+
+fn parse_tree(input: &str) -> Result<MetadataTree, ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let nums = words
+        .iter()
+        .map(|word| parse_number(1, line, word))
+        .collect::<Result<Vec<u32>, ParseError>>()?;
+    let mut nums = nums.into_iter();
+    let tree = MetadataTree::parse(&mut nums).ok_or_else(|| {
+        ParseError::new(1, line.chars().count() + 1, "", "the tree ends too soon")
+    })?;
+    let used = words.len() - nums.len();
+    if used < words.len() {
+        return Err(ParseError::at(
+            1,
+            line,
+            words[used],
+            "there's more after the end of the tree",
+        ));
+    }
+    Ok(tree)
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let metadata: Vec<u32> = parse_tree(input)?.all_metadata();
+        Ok(Answer::new(metadata.iter().sum::<u32>()))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        Ok(Answer::new(parse_tree(input)?.value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree() {
+        let tree = parse_tree("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap();
+        assert_eq!(138, tree.all_metadata().iter().sum::<u32>());
+        assert_eq!(66, tree.value());
+    }
+
+    #[test]
+    fn test_bad_tree() {
+        let err = parse_tree("2 3 0 3 10 11 12 1 1 0 1 99 2").err().unwrap();
+        assert_eq!(30, err.column);
+        let err = parse_tree("0 1 5 6").err().unwrap();
+        assert_eq!((7, "6".to_string()), (err.column, err.text));
+        let err = parse_tree("0 1 x").err().unwrap();
+        assert_eq!(5, err.column);
+    }
+}
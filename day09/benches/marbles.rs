@@ -0,0 +1,20 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day9::play_game_ring;
+
+fn bench_play_game_ring(c: &mut Criterion) {
+    let mut group = c.benchmark_group("play_game_ring");
+    for &last_points in &[1_000, 10_000, 100_000] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(last_points),
+            &last_points,
+            |b, &last_points| b.iter(|| play_game_ring(425, last_points)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_play_game_ring);
+criterion_main!(benches);
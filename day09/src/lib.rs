@@ -0,0 +1,263 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{parse_numbers, Answer, ParseError, Solution};
+use log::{debug, info, trace};
+use std::collections::HashMap;
+
+fn format_marbles(marbles: &[usize], current: usize) -> String {
+    let mut s = String::new();
+    for (idx, marble) in marbles.iter().enumerate() {
+        if idx == current {
+            s += &format!("({})", marble);
+        } else {
+            s += &format!(" {} ", marble);
+        }
+    }
+    s
+}
+
+fn format_marbles_ring(marbles: &MarbleRing, current: usize) -> String {
+    let mut s = String::new();
+    let mut now = marbles.1;
+    let mut remaining = marbles.len();
+    while remaining > 0 {
+        if now == current {
+            s += &format!("({})", now);
+        } else {
+            s += &format!(" {} ", now);
+        }
+        now = marbles.next(&now, 1);
+        remaining -= 1;
+    }
+    s
+}
+
+struct Marble {
+    number: usize,
+    // The next and previous marble in the ring.
+    next: usize,
+    prev: usize,
+}
+
+impl Marble {
+    fn new(number: usize, next: usize, prev: usize) -> Marble {
+        Marble { number, next, prev }
+    }
+}
+
+struct MarbleRing(HashMap<usize, Marble>, usize);
+
+impl MarbleRing {
+    fn new() -> MarbleRing {
+        let mut ring = HashMap::new();
+        ring.insert(0, Marble::new(0, 0, 0));
+        MarbleRing(ring, 0)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn next(&self, spot: &usize, steps: usize) -> usize {
+        let mut spot = self.0.get(spot).unwrap();
+        let mut left = steps;
+        while left > 0 {
+            spot = self.0.get(&spot.next).unwrap();
+            left -= 1;
+        }
+        spot.number
+    }
+
+    fn prev(&self, spot: &usize, steps: usize) -> usize {
+        let mut spot = self.0.get(spot).unwrap();
+        let mut left = steps;
+        while left > 0 {
+            spot = self.0.get(&spot.prev).unwrap();
+            left -= 1;
+        }
+        spot.number
+    }
+
+    // Insert marble `new` clockwise of `exist`
+    fn insert(&mut self, exist: usize, new: usize) {
+        let next_num: usize;
+        {
+            let exist = self.0.get_mut(&exist).unwrap();
+            next_num = exist.next;
+            exist.next = new;
+        }
+        {
+            let new_marble = Marble::new(new, next_num, exist);
+            self.0.insert(new, new_marble);
+        }
+        {
+            let next = self.0.get_mut(&next_num).unwrap();
+            next.prev = new;
+        }
+    }
+
+    // Remove the marble numbered `gone`.
+    // Returns the number of the next marble in the ring.
+    fn remove(&mut self, gone: usize) -> usize {
+        let prev_num: usize;
+        let next_num: usize;
+        let removing;
+        {
+            removing = self.0.remove(&gone).unwrap();
+            prev_num = removing.prev;
+            next_num = removing.next;
+        }
+        {
+            let prev = self.0.get_mut(&removing.prev).unwrap();
+            prev.next = next_num;
+        }
+        {
+            let next = self.0.get_mut(&removing.next).unwrap();
+            next.prev = prev_num;
+        }
+        if self.1 == removing.number {
+            self.1 = next_num;
+        }
+
+        next_num
+    }
+}
+
+/// Plays the marble game, and returns the winning elf's score.
+pub fn play_game_ring(players: usize, last_points: usize) -> u32 {
+    let mut marbles = MarbleRing::new();
+
+    let mut scores = vec![0; players];
+
+    let mut current_player = 1;
+    let mut next_marble = 1;
+    let mut current_marble = 0;
+    while next_marble <= last_points {
+        if next_marble % 23 == 0 {
+            scores[current_player] += next_marble;
+            let to_remove = marbles.prev(&current_marble, 7);
+            scores[current_player] += to_remove;
+            current_marble = marbles.remove(to_remove);
+        } else {
+            let add_spot = marbles.next(&current_marble, 1);
+            marbles.insert(add_spot, next_marble);
+            current_marble = next_marble;
+        }
+        next_marble += 1;
+        current_player = (current_player + 1) % players;
+        if next_marble % 100 == 0 {
+            trace!("Placed {} marbles", next_marble);
+        }
+        trace!("{}", format_marbles_ring(&marbles, current_marble));
+    }
+
+    let mut high_score = 0;
+
+    for (idx, score) in scores.iter().enumerate() {
+        debug!("Score for player {} is {}", idx, score);
+        if score > &high_score {
+            high_score = *score;
+        }
+    }
+    info!("High Score is {}", high_score);
+    high_score as u32
+}
+
+#[allow(dead_code)]
+fn play_game(players: usize, last_points: usize) -> u32 {
+    let mut current_player = 0;
+
+    let mut scores = vec![0; players];
+
+    if last_points < 23 {
+        return 0;
+    }
+
+    // The first two marbles don't wrap around the circle, so start with them placed.
+    let mut marbles: Vec<usize> = vec![0, 2, 1];
+    let mut current_marble_idx = 1;
+    let mut next_marble = 3;
+    trace!("{}", format_marbles(&marbles, current_marble_idx));
+
+    marbles.reserve(last_points);
+
+    while next_marble <= last_points {
+        if next_marble % 23 == 0 {
+            // Don't place it, it's scored!
+            scores[current_player] += next_marble;
+            let remove_idx = (current_marble_idx + marbles.len() - 7) % marbles.len();
+            let removed = marbles.remove(remove_idx);
+            scores[current_player] += removed;
+            current_marble_idx = remove_idx;
+        } else {
+            let next_marble_idx = (current_marble_idx + 2) % marbles.len();
+            marbles.insert(next_marble_idx, next_marble);
+            current_marble_idx = next_marble_idx;
+        }
+        if next_marble % 100 == 0 {
+            debug!("Placed {} marbles", next_marble);
+        }
+        next_marble += 1;
+        current_player = (current_player + 1) % players;
+        trace!("{}", format_marbles(&marbles, current_marble_idx));
+    }
+
+    let mut high_score = 0;
+
+    for (idx, score) in scores.iter().enumerate() {
+        debug!("Score for player {} is {}", idx, score);
+        if score > &high_score {
+            high_score = *score;
+        }
+    }
+    info!("High Score is {}", high_score);
+    high_score as u32
+}
+
+/// Returns the number of players and the value of the last marble.
+/// Parses "N players; last marble is worth M points".
+fn parse_game(input: &str) -> Result<(usize, usize), ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    let nums: Vec<usize> = parse_numbers(1, line, 2)?;
+    Ok((nums[0], nums[1]))
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (players, last_points) = parse_game(input)?;
+        Ok(Answer::new(play_game_ring(players, last_points)))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (players, last_points) = parse_game(input)?;
+        Ok(Answer::new(play_game_ring(players, last_points * 100)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game() {
+        assert_eq!(32, play_game(9, 25));
+        assert_eq!(8317, play_game(10, 1618));
+        assert_eq!(146373, play_game(13, 7999));
+        assert_eq!(2764, play_game(17, 1104));
+        assert_eq!(54718, play_game(21, 6111));
+        assert_eq!(37305, play_game(30, 5807));
+    }
+
+    #[test]
+    fn test_game_ring() {
+        assert_eq!(32, play_game_ring(9, 25));
+        assert_eq!(8317, play_game_ring(10, 1618));
+        assert_eq!(146373, play_game_ring(13, 7999));
+        assert_eq!(2764, play_game_ring(17, 1104));
+        assert_eq!(54718, play_game_ring(21, 6111));
+        assert_eq!(37305, play_game_ring(30, 5807));
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, Answer, ParseError, Solution};
+
+struct Star {
+    position: (i32, i32),
+    velocity: (i32, i32),
+}
+
+impl Star {
+    fn new(x: i32, y: i32, velx: i32, vely: i32) -> Star {
+        Star {
+            position: (x, y),
+            velocity: (velx, vely),
+        }
+    }
+
+    fn next(&self) -> Star {
+        let new = Star::new(
+            self.position.0 + self.velocity.0,
+            self.position.1 + self.velocity.1,
+            self.velocity.0,
+            self.velocity.1,
+        );
+        //println!("Star: Old({}, {}) + ({}, {}) => ({}, {})",
+        //          self.position.0, self.position.1,
+        //          self.velocity.0, self.velocity.1,
+        //          new.position.0, new.position.1);
+        new
+    }
+}
+
+fn min_x(field: &[Star]) -> i32 {
+    field
+        .iter()
+        .min_by(|x, y| x.position.0.cmp(&y.position.0))
+        .unwrap()
+        .position
+        .0
+}
+
+fn max_x(field: &[Star]) -> i32 {
+    field
+        .iter()
+        .max_by(|x, y| x.position.0.cmp(&y.position.0))
+        .unwrap()
+        .position
+        .0
+}
+
+fn min_y(field: &[Star]) -> i32 {
+    field
+        .iter()
+        .min_by(|x, y| x.position.1.cmp(&y.position.1))
+        .unwrap()
+        .position
+        .1
+}
+
+fn max_y(field: &[Star]) -> i32 {
+    field
+        .iter()
+        .max_by(|x, y| x.position.1.cmp(&y.position.1))
+        .unwrap()
+        .position
+        .1
+}
+
+/// Draws the stars in the sky, with '#' for a star.
+fn render_stars(field: &[Star]) -> String {
+    let max_x = max_x(field);
+    let min_x = min_x(field);
+    let max_y = max_y(field);
+    let min_y = min_y(field);
+
+    let rowspan = max_x - min_x + 1;
+    let skylen = (max_y - min_y + 1) * rowspan;
+    let mut sky = Vec::new();
+    sky.resize(skylen as usize, '.');
+
+    for s in field {
+        let idx = (s.position.0 - min_x) + (s.position.1 - min_y) * rowspan;
+        sky[idx as usize] = '#';
+    }
+
+    let mut rendered = String::new();
+    let mut idx = 0;
+    while idx < skylen {
+        let s: String = sky[idx as usize..(idx + rowspan) as usize].iter().collect();
+        rendered.push('\n');
+        rendered.push_str(&s);
+        idx += rowspan;
+    }
+    rendered
+}
+
+fn parse_stars(input: &str) -> Result<Vec<Star>, ParseError> {
+    let stars: Vec<Star> = numbered_lines(input)
+        .map(|(line_no, line)| {
+            let nums = parse_numbers(line_no, line, 4)?;
+            Ok(Star::new(nums[0], nums[1], nums[2], nums[3]))
+        })
+        .collect::<Result<_, ParseError>>()?;
+    if stars.is_empty() {
+        return Err(ParseError::line(1, "", "there are no stars"));
+    }
+    Ok(stars)
+}
+
+/// Moves the stars along until they are closest together, which is when they spell the message.
+/// Returns the stars at that point and the number of seconds it took.  Stars that never get any
+/// closer together are already there.
+fn align(mut stars: Vec<Star>) -> (Vec<Star>, usize) {
+    let spread = |stars: &[Star]| (max_x(stars) - min_x(stars)) + (max_y(stars) - min_y(stars));
+
+    let mut secs = 0;
+    loop {
+        let next: Vec<Star> = stars.iter().map(|x| x.next()).collect();
+        if spread(&next) >= spread(&stars) {
+            return (stars, secs);
+        }
+        stars = next;
+        secs += 1;
+    }
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (stars, _) = align(parse_stars(input)?);
+        Ok(Answer::new(render_stars(&stars)))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (_, secs) = align(parse_stars(input)?);
+        Ok(Answer::new(secs))
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day11::most_powerful;
+
+/// `most_powerful` fills in `power_grid` for every square size up to the biggest one.
+fn bench_power_grid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("power_grid");
+    group.sample_size(10);
+    for &max_size in &[3, 10, 20] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(max_size),
+            &max_size,
+            |b, &max_size| b.iter(|| most_powerful(18, 1..=max_size)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_power_grid);
+criterion_main!(benches);
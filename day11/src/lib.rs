@@ -0,0 +1,120 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{parse_number, Answer, ParseError, Solution};
+use log::debug;
+use std::{collections::HashMap, ops::RangeInclusive};
+
+fn power_lvl(x: i32, y: i32, serial: i32) -> i32 {
+    let rack_id = x + 10;
+    let mut power = rack_id * y;
+    power += serial;
+    power *= rack_id;
+    let hund_digit = power / 100 % 10;
+    hund_digit - 5
+}
+
+fn power_grid(
+    top_left_x: i32,
+    top_left_y: i32,
+    size: i32,
+    serial: i32,
+    memo: &mut HashMap<(i32, i32, i32), i32>,
+) -> i32 {
+    if let Some(size) = memo.get(&(top_left_x, top_left_y, size)) {
+        return *size;
+    }
+    let result = match size {
+        1 => power_lvl(top_left_x, top_left_y, serial),
+        2 => {
+            power_lvl(top_left_x, top_left_y, serial)
+                + power_lvl(top_left_x + 1, top_left_y, serial)
+                + power_lvl(top_left_x, top_left_y + 1, serial)
+                + power_lvl(top_left_x + 1, top_left_y + 1, serial)
+        }
+        _ => {
+            let tl = memo.get(&(top_left_x, top_left_y, size - 1)).unwrap();
+            let br = memo
+                .get(&(top_left_x + 1, top_left_y + 1, size - 1))
+                .unwrap();
+            let tr = power_lvl(top_left_x + size - 1, top_left_y, serial);
+            let bl = power_lvl(top_left_x, top_left_y + size - 1, serial);
+            let center = memo
+                .get(&(top_left_x + 1, top_left_y + 1, size - 2))
+                .unwrap();
+            tl + br + tr + bl - center
+        }
+    };
+    memo.insert((top_left_x, top_left_y, size), result);
+    result
+}
+
+/// Finds the square with the most power out of all the sizes in `sizes`.
+/// Returns the top left x, y, and size of the square.
+pub fn most_powerful(serial: i32, sizes: RangeInclusive<i32>) -> (i32, i32, i32) {
+    let mut max_grid = 0;
+    let mut max_x = 0;
+    let mut max_y = 0;
+    let mut max_size = 0;
+
+    let mut memo = HashMap::new();
+
+    // Every smaller size gets filled in since bigger grids are calculated from them.
+    for size in 1..=*sizes.end() {
+        for topleft_x in 1..(302 - size) {
+            for topleft_y in 1..(302 - size) {
+                let grid = power_grid(topleft_x, topleft_y, size, serial, &mut memo);
+                if sizes.contains(&size) && grid > max_grid {
+                    max_x = topleft_x;
+                    max_y = topleft_y;
+                    max_size = size;
+                    max_grid = grid;
+                    debug!(
+                        "Grid at {},{} size {} has {} power",
+                        topleft_x, topleft_y, size, grid
+                    );
+                }
+            }
+        }
+    }
+
+    (max_x, max_y, max_size)
+}
+
+fn parse_serial(input: &str) -> Result<i32, ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    parse_number(1, line, line)
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (x, y, _) = most_powerful(parse_serial(input)?, 3..=3);
+        Ok(Answer::new(format!("{},{}", x, y)))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (x, y, size) = most_powerful(parse_serial(input)?, 1..=299);
+        Ok(Answer::new(format!("{},{},{}", x, y, size)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_level() {
+        assert_eq!(4, power_lvl(3, 5, 8));
+        assert_eq!(-5, power_lvl(122, 79, 57));
+        assert_eq!(0, power_lvl(217, 196, 39));
+        assert_eq!(4, power_lvl(101, 153, 71));
+    }
+
+    #[test]
+    fn test_most_powerful() {
+        assert_eq!((33, 45, 3), most_powerful(18, 3..=3));
+        assert_eq!((21, 61, 3), most_powerful(42, 3..=3));
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, Answer, ParseError, Solution};
+use log::{debug, info};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+/// The five pot patterns which lead to a plant in the middle pot in the next generation.
+type Rules = HashSet<String>;
+
+struct Pots(VecDeque<i32>);
+
+impl Pots {
+    fn new(garden: &str) -> Pots {
+        Pots(
+            garden
+                .bytes()
+                .enumerate()
+                .filter(|(_i, x)| *x == b'#')
+                .map(|(i, _)| i as i32)
+                .collect(),
+        )
+    }
+
+    fn min_pot(&self) -> i32 {
+        *self.0.front().unwrap()
+    }
+
+    fn max_pot(&self) -> i32 {
+        *self.0.back().unwrap()
+    }
+
+    fn pot_at(&self, at: i32) -> bool {
+        for idx in self.0.iter() {
+            if idx == &at {
+                return true;
+            }
+            if idx > &at {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn next_generation(&self, rules: &Rules) -> Pots {
+        let mut new_pots = VecDeque::new();
+        let mut from_pot = self.min_pot() - 2;
+        let mut pot_scan = VecDeque::new();
+        pot_scan.push_back('.');
+        pot_scan.push_back('.');
+        pot_scan.push_back('.');
+        pot_scan.push_back('.');
+        pot_scan.push_back('#');
+        while from_pot < self.max_pot() + 2 {
+            let window: String = pot_scan.iter().collect();
+            if rules.contains(&window) {
+                new_pots.push_back(from_pot)
+            }
+            from_pot += 1;
+            pot_scan.pop_front();
+            if self.pot_at(from_pot + 2) {
+                pot_scan.push_back('#');
+            } else {
+                pot_scan.push_back('.');
+            }
+        }
+        Pots(new_pots)
+    }
+}
+
+impl fmt::Display for Pots {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::new();
+        for idx in self.min_pot()..(self.max_pot() + 1) {
+            if self.pot_at(idx) {
+                s.push('#');
+            } else {
+                s.push('.');
+            }
+        }
+        write!(f, "{}", s)
+    }
+}
+
+fn sum(pots: &Pots) -> i64 {
+    pots.0.iter().map(|&x| i64::from(x)).sum()
+}
+
+/// How many generations in a row the sum has to grow by the same amount before we decide that
+/// it will keep growing that way forever.
+const STEADY_GENERATIONS: usize = 100;
+
+fn sum_after_generations(garden: &str, rules: &Rules, gens: usize) -> i64 {
+    let mut state = Pots::new(garden);
+
+    debug!("{}: {}", 0, state);
+    let mut last_growth = 0;
+    let mut steady_for = 0;
+    for gen in 0..gens {
+        let last_sum = sum(&state);
+        state = state.next_generation(rules);
+        debug!("{}: {}", gen, state);
+        let sum = sum(&state);
+        debug!("Sum of {} locations: {}", state.0.len(), sum);
+        // Eventually the plants settle into a pattern that just moves along.
+        if sum - last_sum == last_growth {
+            steady_for += 1;
+        } else {
+            last_growth = sum - last_sum;
+            steady_for = 0;
+        }
+        if steady_for == STEADY_GENERATIONS {
+            info!("Growing by {} every generation from now on", last_growth);
+            return sum + last_growth * (gens - gen - 1) as i64;
+        }
+    }
+
+    sum(&state)
+}
+
+/// Parses the initial state and the rules that grow a plant.
+/// Checks that `pots` is only pots with plants (#) or without (.).
+fn check_pots(line_no: usize, line: &str, pots: &str) -> Result<(), ParseError> {
+    match pots.find(|c| c != '#' && c != '.') {
+        Some(idx) => Err(ParseError::at(
+            line_no,
+            line,
+            &pots[idx..idx + 1],
+            "pots are either # or .",
+        )),
+        None => Ok(()),
+    }
+}
+
+fn parse_garden(input: &str) -> Result<(&str, Rules), ParseError> {
+    let mut lines = numbered_lines(input);
+    let (line_no, line) = lines.next().unwrap_or((1, ""));
+    let garden = match line.strip_prefix("initial state: ") {
+        Some(garden) => garden.trim(),
+        None => {
+            return Err(ParseError::line(
+                line_no,
+                line,
+                "expected \"initial state: \" and the pots",
+            ))
+        }
+    };
+    check_pots(line_no, line, garden)?;
+    let mut rules = Rules::new();
+    for (line_no, line) in lines.filter(|(_, line)| !line.trim().is_empty()) {
+        let (pattern, result) = match line.split_once(" => ") {
+            Some((pattern, result)) if pattern.len() == 5 && result.len() == 1 => (pattern, result),
+            _ => {
+                return Err(ParseError::line(
+                    line_no,
+                    line,
+                    "expected a rule like \"..#.. => #\"",
+                ))
+            }
+        };
+        check_pots(line_no, line, pattern)?;
+        check_pots(line_no, line, result)?;
+        if result == "#" {
+            rules.insert(pattern.to_string());
+        }
+    }
+    Ok((garden, rules))
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (garden, rules) = parse_garden(input)?;
+        Ok(Answer::new(sum_after_generations(garden, &rules, 20)))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (garden, rules) = parse_garden(input)?;
+        Ok(Answer::new(sum_after_generations(
+            garden,
+            &rules,
+            50_000_000_000,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = "initial state: #..#.#..##......###...###
+
+...## => #
+..#.. => #
+.#... => #
+.#.#. => #
+.#.## => #
+.##.. => #
+.#### => #
+#.#.# => #
+#.### => #
+##.#. => #
+##.## => #
+###.. => #
+###.# => #
+####. => #
+";
+        let (garden, rules) = parse_garden(input).unwrap();
+        assert_eq!(325, sum_after_generations(garden, &rules, 20));
+
+        let err = parse_garden(&input.replace("..#.. => #", "..#. => #"))
+            .err()
+            .unwrap();
+        assert_eq!(4, err.line);
+        assert!(parse_garden("initial state: #..x").is_err());
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{
+    grid::{Grid, Point},
+    Answer, ParseError, Solution,
+};
+use log::{debug, info, trace};
+use std::collections::VecDeque;
+
+#[derive(PartialEq)]
+enum CartTurnState {
+    Left,
+    GayilyForward,
+    Right,
+}
+
+impl CartTurnState {
+    fn next(&self) -> Self {
+        match self {
+            CartTurnState::Left => CartTurnState::GayilyForward,
+            CartTurnState::GayilyForward => CartTurnState::Right,
+            CartTurnState::Right => CartTurnState::Left,
+        }
+    }
+}
+
+struct Cart {
+    loc: Point,
+    direction: char,
+    next_turn: CartTurnState,
+    last_tick: i32,
+}
+
+impl Cart {
+    fn new(loc: Point, direction: char) -> Cart {
+        Cart {
+            loc,
+            direction,
+            next_turn: CartTurnState::Left,
+            last_tick: 0,
+        }
+    }
+
+    fn cart_char(c: &char) -> bool {
+        matches!(c, '^' | '>' | '<' | 'v')
+    }
+
+    fn to_track(c: &char) -> char {
+        match c {
+            '^' | 'v' => '|',
+            '<' | '>' => '-',
+            r => *r,
+        }
+    }
+
+    // Makes the cart travel along the map one step
+    // Returns the new location of the cart.
+    fn go(&mut self, map: &Grid<char>) -> Point {
+        // Move!
+        self.loc = match self.direction {
+            '>' => (self.loc.0 + 1, self.loc.1),
+            '<' => (self.loc.0 - 1, self.loc.1),
+            'v' => (self.loc.0, self.loc.1 + 1),
+            '^' => (self.loc.0, self.loc.1 - 1),
+            _ => unreachable!(),
+        };
+        // Change direction now
+        self.direction = match (map[self.loc], self.direction) {
+            ('-', _) | ('|', _) => self.direction,
+            ('\\', '<') => '^',
+            ('\\', 'v') => '>',
+            ('\\', '>') => 'v',
+            ('\\', '^') => '<',
+            ('/', '<') => 'v',
+            ('/', '^') => '>',
+            ('/', '>') => '^',
+            ('/', 'v') => '<',
+            ('+', c) => {
+                let dir = match (&self.next_turn, c) {
+                    (CartTurnState::Left, '^') => '<',
+                    (CartTurnState::Left, '<') => 'v',
+                    (CartTurnState::Left, 'v') => '>',
+                    (CartTurnState::Left, '>') => '^',
+                    (CartTurnState::GayilyForward, c) => c,
+                    (CartTurnState::Right, '^') => '>',
+                    (CartTurnState::Right, '<') => '^',
+                    (CartTurnState::Right, 'v') => '<',
+                    (CartTurnState::Right, '>') => 'v',
+                    _ => unreachable!(),
+                };
+                self.next_turn = self.next_turn.next();
+                dir
+            }
+            _ => unreachable!(),
+        };
+        self.last_tick += 1;
+        self.loc
+    }
+}
+
+fn format_map(map: &Grid<char>, carts: &VecDeque<Cart>) -> String {
+    let mut s = String::new();
+    for (p, track) in map.iter() {
+        let c = match carts.iter().find(|c| c.loc == p) {
+            Some(c) => c.direction,
+            None => *track,
+        };
+        s.push(c);
+        if p.0 == map.max().0 {
+            s.push('\n');
+        }
+    }
+    s
+}
+
+fn parse_tracks(input: &str) -> Result<(Grid<char>, VecDeque<Cart>), ParseError> {
+    // Trailing spaces tend to get lost, so put them back to make all the rows the same length.
+    let width = input.lines().map(|line| line.chars().count()).max();
+    let padded: String = input
+        .lines()
+        .map(|line| format!("{:1$}\n", line, width.unwrap_or(0)))
+        .collect();
+    let mut map = Grid::parse(&padded, |c| {
+        if Cart::cart_char(&c) || "-|/\\+ ".contains(c) {
+            Ok(c)
+        } else {
+            Err("not a track or a cart")
+        }
+    })?;
+
+    let mut carts: VecDeque<Cart> = VecDeque::new();
+    for p in map.points().collect::<Vec<_>>() {
+        if Cart::cart_char(&map[p]) {
+            carts.push_back(Cart::new(p, map[p]));
+            map[p] = Cart::to_track(&map[p]);
+        }
+    }
+    if carts.len() < 2 {
+        return Err(ParseError::new(
+            1,
+            1,
+            "",
+            "there need to be at least two carts",
+        ));
+    }
+    Ok((map, carts))
+}
+
+/// Runs the carts around the tracks until there is only one left, removing carts that crash.
+/// Returns the location of the first crash, and the location of the last cart.
+fn run_carts(map: &Grid<char>, mut carts: VecDeque<Cart>) -> (Point, Point) {
+    let mut first_collision = None;
+    let mut tick: i32 = -1;
+    loop {
+        // If we are at the next tick, sort them in order.
+        if carts.front().unwrap().last_tick != tick {
+            tick += 1;
+            if carts.len() == 1 {
+                info!("Only one cart left at tick {}", tick);
+                return (first_collision.unwrap(), carts.front().unwrap().loc);
+            }
+            trace!("Tick {}:\n{}", tick, format_map(map, &carts));
+            let (front, back) = carts.as_mut_slices();
+            front.sort_unstable_by_key(|a| a.loc);
+            back.sort_unstable_by_key(|a| a.loc);
+        }
+        let mut moving_cart = carts.pop_front().unwrap();
+        let new_loc = moving_cart.go(map);
+        // Did we collide
+        let mut idx_remove = None;
+        if let Some((idx, _)) = carts.iter().enumerate().find(|(_, c)| c.loc == new_loc) {
+            debug!(
+                "Collision at tick {} at {:?}",
+                moving_cart.last_tick, moving_cart.loc
+            );
+            first_collision.get_or_insert(moving_cart.loc);
+            idx_remove = Some(idx);
+        }
+        if let Some(idx) = idx_remove.take() {
+            carts.remove(idx);
+        } else {
+            carts.push_back(moving_cart);
+        }
+    }
+}
+
+fn format_loc(loc: Point) -> String {
+    format!("{},{}", loc.0, loc.1)
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (map, carts) = parse_tracks(input)?;
+        let (first_collision, _) = run_carts(&map, carts);
+        Ok(Answer::new(format_loc(first_collision)))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (map, carts) = parse_tracks(input)?;
+        let (_, last_cart) = run_carts(&map, carts);
+        Ok(Answer::new(format_loc(last_cart)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = r"/>-<\
+|   |
+| /<+-\
+| | | v
+\>+</ |
+  |   ^
+  \<->/
+";
+        assert_eq!("2,0", Day13.part1(input).unwrap().to_string());
+        assert_eq!("6,4", Day13.part2(input).unwrap().to_string());
+        assert!(parse_tracks("-->-x").is_err());
+        assert!(parse_tracks("-->--").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{synthetic::XorShift, ParseError};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use day15::{Battle, Faction};
+
+/// A square cave `dim` squares on a side inside its walls, with walls, elves and goblins
+/// scattered about at random.
+fn cave(dim: usize) -> String {
+    let mut rng = XorShift::new(15);
+    let wall = "#".repeat(dim + 2);
+    let mut cave = format!("{}\n", wall);
+    for _ in 0..dim {
+        let row: String = (0..dim)
+            .map(|_| rng.pick(b"..................#EG") as char)
+            .collect();
+        cave.push_str(&format!("#{}#\n", row));
+    }
+    cave.push_str(&wall);
+    cave
+}
+
+/// Plays a round of a battle, one way or another.
+type Round = fn(&mut Battle) -> Result<Option<Vec<char>>, ParseError>;
+
+/// Five rounds on each cave, finding paths with one search a move, and with the two searches a
+/// move it used to take.
+fn bench_rounds(c: &mut Criterion) {
+    let factions = [Faction::new('E', 200, 3), Faction::new('G', 200, 3)];
+    let mut group = c.benchmark_group("five_rounds");
+    group.sample_size(10);
+    for &dim in &[16, 32, 48] {
+        let cave = cave(dim);
+        let searches: [(&str, Round); 2] = [
+            ("one_search", Battle::round),
+            ("two_searches", Battle::round_searching_twice),
+        ];
+        for &(name, round) in &searches {
+            group.bench_with_input(BenchmarkId::new(name, dim), &cave, |b, cave| {
+                b.iter_batched(
+                    || Battle::new(cave, &factions).unwrap(),
+                    |mut battle| {
+                        for _ in 0..5 {
+                            round(&mut battle).unwrap();
+                        }
+                        battle
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_rounds);
+criterion_main!(benches);
@@ -0,0 +1,877 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{
+    grid::{reading_order, Grid, Point},
+    numbered_lines, Answer, ParseError, Solution,
+};
+use log::{debug, info, trace};
+use std::{cmp::Ordering, collections::BTreeMap, fmt};
+
+mod playback;
+mod replay;
+
+pub use crate::{
+    playback::{play, Command, Frame, FrameUnit, Player},
+    replay::{record_battle, verify_battle, Attack, Divergence, Turn},
+};
+
+#[derive(Clone)]
+struct Unit {
+    /// Which unit it is, counting in reading order at the start of the battle.
+    id: usize,
+    pos: Point,
+    team: char,
+    hitpoints: i64,
+    power: i64,
+}
+
+const INITIAL_HITPOINTS: i64 = 200;
+
+/// A side in a battle: the letter its units have on the map, how much they can take and how hard
+/// they hit, and who they're on the side of.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Faction {
+    pub symbol: char,
+    pub hitpoints: i64,
+    pub power: i64,
+    /// The factions it doesn't fight.  Either side naming the other is enough.
+    pub allies: Vec<char>,
+}
+
+impl Faction {
+    pub fn new(symbol: char, hitpoints: i64, power: i64) -> Faction {
+        Faction {
+            symbol,
+            hitpoints,
+            power,
+            allies: Vec::new(),
+        }
+    }
+
+    pub fn with_allies(mut self, allies: &[char]) -> Faction {
+        self.allies = allies.to_vec();
+        self
+    }
+}
+
+/// The puzzle's elves, hitting for `elves_power`, against its goblins.
+fn elves_and_goblins(elves_power: i64) -> Vec<Faction> {
+    vec![
+        Faction::new('E', INITIAL_HITPOINTS, elves_power),
+        Faction::new('G', INITIAL_HITPOINTS, 3),
+    ]
+}
+
+impl Unit {
+    fn new(id: usize, pos: Point, faction: &Faction) -> Unit {
+        Unit {
+            id,
+            pos,
+            team: faction.symbol,
+            power: faction.power,
+            hitpoints: faction.hitpoints,
+        }
+    }
+
+    fn reading_order_cmp(&self, other: &Unit) -> Ordering {
+        reading_order(&self.pos, &other.pos)
+    }
+
+    fn in_range(&self, other: &Unit) -> bool {
+        if other.hitpoints <= 0 {
+            false
+        } else {
+            (self.pos.0 - other.pos.0).abs() + (self.pos.1 - other.pos.1).abs() == 1
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}({}) at ({}, {})",
+            self.team, self.hitpoints, self.pos.0, self.pos.1
+        )?;
+        Ok(())
+    }
+}
+
+struct Map {
+    walls: Grid<bool>,
+    /// Whether there's a wall or a unit that's still alive on each square.
+    occupied: Grid<bool>,
+    units: Vec<Unit>,
+    factions: Vec<Faction>,
+    /// How many units each faction has lost.
+    casualties: BTreeMap<char, usize>,
+    /// Stop the round as soon as an elf dies.
+    flawless: bool,
+}
+
+impl Map {
+    fn new(cave: &Grid<char>, factions: &[Faction]) -> Map {
+        let mut units = Vec::new();
+        // Find all the units, and store the layout.
+        for (pos, c) in cave.iter() {
+            if let Some(faction) = factions.iter().find(|f| f.symbol == *c) {
+                units.push(Unit::new(units.len(), pos, faction));
+            }
+        }
+        let mut occupied = cave.map(|&c| c == '#');
+        for unit in &units {
+            occupied[unit.pos] = true;
+        }
+        Map {
+            units,
+            walls: cave.map(|&c| c == '#'),
+            occupied,
+            factions: factions.to_vec(),
+            casualties: factions.iter().map(|f| (f.symbol, 0)).collect(),
+            flawless: false,
+        }
+    }
+
+    fn elves_lost(&self) -> usize {
+        self.casualties.get(&'E').copied().unwrap_or(0)
+    }
+
+    /// Whether the faction with `a` names the one with `b` as an ally, or the other way round.
+    /// Alliances aren't transitive: two factions allied with the same third one still fight each
+    /// other unless one of them names the other.
+    fn allied(&self, a: char, b: char) -> bool {
+        self.factions.iter().any(|f| {
+            (f.symbol == a && f.allies.contains(&b)) || (f.symbol == b && f.allies.contains(&a))
+        })
+    }
+
+    fn is_enemy(&self, unit: &Unit, other: &Unit) -> bool {
+        other.hitpoints > 0 && unit.team != other.team && !self.allied(unit.team, other.team)
+    }
+
+    /// The factions with units still standing, in the order of the table.
+    fn standing(&self) -> Vec<char> {
+        self.factions
+            .iter()
+            .map(|f| f.symbol)
+            .filter(|&symbol| {
+                self.units
+                    .iter()
+                    .any(|u| u.hitpoints > 0 && u.team == symbol)
+            })
+            .collect()
+    }
+
+    /// Whether none of the factions still standing are enemies of each other.
+    fn is_over(&self) -> bool {
+        let standing = self.standing();
+        standing
+            .iter()
+            .enumerate()
+            .all(|(at, &a)| standing[..at].iter().all(|&b| self.allied(a, b)))
+    }
+
+    fn unit_at(&self, pos: Point) -> Option<&Unit> {
+        self.units.iter().find(|u| u.hitpoints > 0 && u.pos == pos)
+    }
+
+    fn is_occupied(&self, pos: Point) -> bool {
+        self.occupied.get(pos) != Some(&false)
+    }
+
+    /// Where the unit at `start` steps to head for the nearest of the `wanted` squares, or None
+    /// if it can't get to any.  The nearest square is the first in reading order of the ones
+    /// that are as near as each other, and the step is the first in reading order of the ones
+    /// that start a shortest path there.
+    ///
+    /// That's all one search out from `start` a distance at a time, where each square keeps the
+    /// first step of the paths that got to it first.
+    fn step_towards(&self, start: Point, wanted: &Grid<bool>) -> Option<Point> {
+        // The first step to each square found so far, and how far it is.
+        let mut found: Grid<Option<(usize, Point)>> = self.walls.map(|_| None);
+        found[start] = Some((0, start));
+        let mut frontier = vec![start];
+        let mut distance = 0;
+        while !frontier.is_empty() {
+            distance += 1;
+            let mut next = Vec::new();
+            for &u in &frontier {
+                let (_, first) = found[u].unwrap();
+                for v in self.walls.neighbors4(u).filter(|&v| !self.is_occupied(v)) {
+                    // The squares next to the start are their own first step.
+                    let first = if u == start { v } else { first };
+                    match found[v] {
+                        None => {
+                            found[v] = Some((distance, first));
+                            next.push(v);
+                        }
+                        Some((d, other))
+                            if d == distance && reading_order(&first, &other).is_lt() =>
+                        {
+                            found[v] = Some((distance, first));
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+            let chosen = next
+                .iter()
+                .filter(|&&v| wanted[v])
+                .min_by(|a, b| reading_order(a, b));
+            if let Some(&chosen) = chosen {
+                return found[chosen].map(|(_, first)| first);
+            }
+            frontier = next;
+        }
+        None
+    }
+
+    /// The same as `step_towards`, the way it used to be found: a search for the distance to
+    /// every square to choose the nearest, and another back from that square to choose the step.
+    /// It's only here for the tests and the benches to compare with.
+    #[cfg(any(test, feature = "bench"))]
+    fn step_towards_searching_twice(&self, start: Point, wanted: &Grid<bool>) -> Option<Point> {
+        let distances_from = |from| {
+            aoc_common::path::bfs(from, |&u| {
+                self.walls
+                    .neighbors4(u)
+                    .filter(|&v| !self.is_occupied(v))
+                    .collect::<Vec<_>>()
+            })
+            .into_distances()
+        };
+        let distance = distances_from(start);
+        let chosen = self
+            .walls
+            .iter()
+            .filter(|&(p, _)| wanted[p] && distance.contains_key(&p))
+            .map(|(p, _)| p)
+            .min_by(|a, b| distance[a].cmp(&distance[b]).then(reading_order(a, b)))?;
+        let distance = distances_from(chosen);
+        let mut next_steps: Vec<_> = self
+            .walls
+            .neighbors4(start)
+            .filter(|p| distance.contains_key(p))
+            .collect();
+        next_steps.sort_by(|a, b| distance[a].cmp(&distance[b]).then(reading_order(a, b)));
+        next_steps.first().copied()
+    }
+
+    /// Plays a round, adding what each unit did to `turns`.  If the battle ended during it,
+    /// returns the alliance that won, which is the factions still standing once a unit has
+    /// nobody left to fight and none of them are enemies.  A unit with nobody to fight while
+    /// other factions are still fighting each other just ends its turn.  If the map is
+    /// `flawless`, the round stops when an elf dies.
+    ///
+    /// If nobody moved or attacked all round, every round after it would go the same way and the
+    /// battle would never end, so that's a fault.  That's how a battle ends where the units
+    /// waiting for their allies to finish can't get at anyone, and neither can their allies.
+    fn round(&mut self, turns: &mut Vec<Turn>) -> Result<Option<Vec<char>>, ParseError> {
+        self.round_with(turns, Map::step_towards)
+    }
+
+    /// Plays a round like `round`, finding where units step with `step_towards`.
+    fn round_with<F>(
+        &mut self,
+        turns: &mut Vec<Turn>,
+        step_towards: F,
+    ) -> Result<Option<Vec<char>>, ParseError>
+    where
+        F: Fn(&Map, Point, &Grid<bool>) -> Option<Point>,
+    {
+        // Sort the units in reading order
+        self.units.sort_unstable_by(|a, b| a.reading_order_cmp(b));
+        let mut acted = false;
+        for idx in 0..self.units.len() {
+            // If the active unit is dead it will be removed after everyone else takes a turn but
+            // it doesn't get a turn.
+            if self.units[idx].hitpoints <= 0 {
+                continue;
+            }
+            let mut active = self.units[idx].clone();
+            let mut turn = Turn {
+                unit: active.id,
+                team: active.team,
+                from: active.pos,
+                to: active.pos,
+                attack: None,
+            };
+
+            // If a target is in range
+            let targets: Vec<usize> = self
+                .units
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| self.is_enemy(&active, x))
+                .map(|(idx, _)| idx)
+                .collect();
+            if targets.is_empty() {
+                if self.is_over() {
+                    // Combat is over, the active unit's alliance won.
+                    return Ok(Some(self.standing()));
+                }
+                turns.push(turn);
+                continue;
+            }
+            let targets_in_range: Vec<_> = targets
+                .iter()
+                .filter(|&&x| active.in_range(&self.units[x]))
+                .collect();
+            if targets_in_range.is_empty() {
+                // No targets in range, movement towards the squares next to them.
+                let mut wanted = self.walls.map(|_| false);
+                for &o in &targets {
+                    for p in self.walls.neighbors4(self.units[o].pos) {
+                        wanted[p] = !self.is_occupied(p);
+                    }
+                }
+                let next_step = match step_towards(self, active.pos, &wanted) {
+                    Some(next_step) => next_step,
+                    None => {
+                        // The unit ends it's turn.
+                        trace!("{} can't move to any enemy, skipping turn", active);
+                        turns.push(turn);
+                        continue;
+                    }
+                };
+                self.occupied[active.pos] = false;
+                self.occupied[next_step] = true;
+                active.pos = next_step;
+                turn.to = next_step;
+                acted = true;
+            }
+            self.units.get_mut(idx).unwrap().pos = active.pos;
+            // After movement, we may have a target in range now. re-target.
+            let mut targets_in_range: Vec<_> = targets
+                .iter()
+                .filter(|&&x| active.in_range(&self.units[x]))
+                .collect();
+            if !targets_in_range.is_empty() {
+                // Already in reading order (since units was in reading order), so stably sorting
+                // by hitpoints means the target to damage will be first.
+                targets_in_range
+                    .sort_by(|&&a, &&b| self.units[a].reading_order_cmp(&self.units[b]));
+                targets_in_range
+                    .sort_by(|&&a, &&b| self.units[a].hitpoints.cmp(&self.units[b].hitpoints));
+                let target = self.units.get_mut(*targets_in_range[0]).unwrap();
+                target.hitpoints -= active.power;
+                turn.attack = Some(Attack {
+                    unit: target.id,
+                    team: target.team,
+                    damage: active.power,
+                });
+                if target.hitpoints <= 0 {
+                    *self.casualties.entry(target.team).or_insert(0) += 1;
+                    self.occupied[target.pos] = false;
+                }
+                trace!("{} attacks {}", active, target);
+                acted = true;
+            }
+            turns.push(turn);
+            if self.flawless && self.elves_lost() > 0 {
+                return Ok(None);
+            }
+        }
+        if !acted {
+            return Err(ParseError::fault(
+                "nobody can get at an enemy, so the battle never ends",
+            ));
+        }
+        // Remove dead units from the board
+        self.units.retain(|x| x.hitpoints > 0);
+        // No one won so far..
+        Ok(None)
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut units_on_line = " ".to_string();
+        for (pos, &wall) in self.walls.iter() {
+            if wall {
+                write!(f, "#")?;
+            } else if let Some(u) = self.unit_at(pos) {
+                write!(f, "{}", u.team)?;
+                units_on_line = format!("{} {}", units_on_line, u);
+            } else {
+                write!(f, ".")?;
+            }
+            if pos.0 == self.walls.max().0 {
+                writeln!(f, "{}", units_on_line)?;
+                units_on_line = " ".to_string();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How a battle ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// Full rounds completed.
+    pub rounds: i64,
+    /// The factions that won.
+    pub winners: Vec<char>,
+    /// Hitpoints the winners have left between them.
+    pub hitpoints: i64,
+    /// How many units each faction lost.
+    pub casualties: BTreeMap<char, usize>,
+}
+
+impl Outcome {
+    /// The puzzle's outcome: full rounds completed times the hitpoints left.
+    pub fn score(&self) -> i64 {
+        self.rounds * self.hitpoints
+    }
+
+    fn answer(&self) -> Answer {
+        Answer::new(self.score())
+            .with_stat("rounds", self.rounds)
+            .with_stat("winner", self.winners.iter().collect::<String>())
+            .with_stat("hitpoints", self.hitpoints)
+            .with_stat("casualties", self.casualties_text())
+    }
+
+    /// The casualties like "E 2, G 10".
+    fn casualties_text(&self) -> String {
+        let lost: Vec<String> = self
+            .casualties
+            .iter()
+            .map(|(symbol, lost)| format!("{} {}", symbol, lost))
+            .collect();
+        lost.join(", ")
+    }
+}
+
+/// Runs the battle on `map` to the end.  Returns how it ended, or None as soon as an elf dies if
+/// `no_elf_deaths` was set, or a fault if it never ends.
+fn battle(mut map: Map, no_elf_deaths: bool) -> Result<Option<Outcome>, ParseError> {
+    map.flawless = no_elf_deaths;
+    let mut rounds = 0;
+    let winners = loop {
+        debug!("Round {} Map:\n{}", rounds, map);
+        rounds += 1;
+        let winners = map.round(&mut Vec::new())?;
+        if map.flawless && map.elves_lost() > 0 {
+            debug!("An elf died in round {}", rounds);
+            return Ok(None);
+        }
+        if let Some(winners) = winners {
+            break winners;
+        }
+    };
+
+    map.units.retain(|x| x.hitpoints > 0);
+    let num_units = map.units.len();
+    info!("There are {} {:?} units left", num_units, winners);
+
+    for unit in &map.units {
+        info!("{}", unit);
+    }
+
+    let sum_hp = map.units.iter().fold(0, |a, x| a + x.hitpoints);
+    info!("Total hitpoints: {}", sum_hp);
+
+    Ok(Some(Outcome {
+        rounds: rounds - 1,
+        winners,
+        hitpoints: sum_hp,
+        casualties: map.casualties,
+    }))
+}
+
+/// Fights the battle in `input` between `factions` to the end, like `Battle::new` sets it up.
+pub fn fight(input: &str, factions: &[Faction]) -> Result<Outcome, ParseError> {
+    Ok(battle(Battle::new(input, factions)?.map, false)?.unwrap())
+}
+
+/// A battle between factions, a round at a time.
+pub struct Battle {
+    map: Map,
+    /// Rounds played, counting the one it ended in.
+    rounds: usize,
+    winners: Option<Vec<char>>,
+    /// Why it can't go on, once a round has gone by without anyone doing anything.
+    fault: Option<ParseError>,
+}
+
+impl Battle {
+    /// The battle in `input` between `factions`, whose symbols are the units on the map.
+    ///
+    /// Panics if two factions have the same symbol, or one is a wall or floor.
+    pub fn new(input: &str, factions: &[Faction]) -> Result<Battle, ParseError> {
+        for (at, faction) in factions.iter().enumerate() {
+            assert!(
+                !"#.".contains(faction.symbol)
+                    && factions[..at].iter().all(|f| f.symbol != faction.symbol),
+                "{} can't be a faction's symbol",
+                faction.symbol
+            );
+        }
+        let cave = parse_map(input, factions)?;
+        Ok(Battle {
+            map: Map::new(&cave, factions),
+            rounds: 0,
+            winners: None,
+            fault: None,
+        })
+    }
+
+    /// Plays a round, unless it's over.  Returns the alliance that won if the battle has ended,
+    /// or a fault if it never will.
+    pub fn round(&mut self) -> Result<Option<Vec<char>>, ParseError> {
+        self.round_with(Map::step_towards)
+    }
+
+    /// Plays a round like `round`, but finds where units step the way it used to be found, with
+    /// two searches instead of one, so the benches can compare the two.  Battles go the same
+    /// either way.
+    #[cfg(any(test, feature = "bench"))]
+    pub fn round_searching_twice(&mut self) -> Result<Option<Vec<char>>, ParseError> {
+        self.round_with(Map::step_towards_searching_twice)
+    }
+
+    fn round_with<F>(&mut self, step_towards: F) -> Result<Option<Vec<char>>, ParseError>
+    where
+        F: Fn(&Map, Point, &Grid<bool>) -> Option<Point>,
+    {
+        if let Some(fault) = &self.fault {
+            return Err(fault.clone());
+        }
+        if self.winners.is_none() {
+            self.rounds += 1;
+            match self.map.round_with(&mut Vec::new(), step_towards) {
+                Ok(winners) => self.winners = winners,
+                Err(fault) => {
+                    self.fault = Some(fault.clone());
+                    return Err(fault);
+                }
+            }
+        }
+        Ok(self.winners.clone())
+    }
+
+    /// Where everything is after the rounds so far.
+    pub fn frame(&self) -> Frame {
+        let max_hitpoints = |team| {
+            let faction = self.map.factions.iter().find(|f| f.symbol == team);
+            faction.map_or(INITIAL_HITPOINTS, |f| f.hitpoints)
+        };
+        Frame {
+            round: self.rounds,
+            walls: self.map.walls.clone(),
+            teams: self.map.factions.iter().map(|f| f.symbol).collect(),
+            units: self
+                .map
+                .units
+                .iter()
+                .filter(|u| u.hitpoints > 0)
+                .map(|u| FrameUnit {
+                    team: u.team,
+                    pos: u.pos,
+                    hitpoints: u.hitpoints,
+                    max_hitpoints: max_hitpoints(u.team),
+                })
+                .collect(),
+            winners: self.winners.clone(),
+            stalemate: self.fault.is_some(),
+        }
+    }
+}
+
+/// How many hits it takes to kill a unit with `power`.
+fn hits_to_kill(power: i64) -> i64 {
+    (INITIAL_HITPOINTS + power - 1) / power
+}
+
+/// The least power the elves need to win without any of them dying, and how that battle ends,
+/// or None if they can't even when they kill with every hit.
+///
+/// Powers that take the same number of hits to kill a goblin fight the same battle, so only the
+/// least power for each number of hits is tried, weakest first.  They can't be searched by
+/// halves: hitting harder changes who's standing where, and can get an elf killed that a weaker
+/// elf wouldn't have been.  Each battle stops as soon as an elf dies, so that's cheap.
+pub fn min_power_for_flawless_victory(input: &str) -> Result<Option<(i64, Outcome)>, ParseError> {
+    let cave = parse_map(input, &elves_and_goblins(3))?;
+    let powers = (3..=INITIAL_HITPOINTS)
+        .filter(|&power| power == 3 || hits_to_kill(power) < hits_to_kill(power - 1));
+    for power in powers {
+        if let Some(outcome) = battle(Map::new(&cave, &elves_and_goblins(power)), true)? {
+            // With no elves on the map the goblins win without losing an elf.
+            if outcome.winners.contains(&'E') {
+                return Ok(Some((power, outcome)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Reads the map of the cave, which has to have walls all the way around, and units from more
+/// than one faction in it.
+fn parse_map(input: &str, factions: &[Faction]) -> Result<Grid<char>, ParseError> {
+    let cave = Grid::parse(input, |c| match c {
+        '#' | '.' => Ok(c),
+        _ if factions.iter().any(|f| f.symbol == c) => Ok(c),
+        _ => Err("not a wall, floor or unit"),
+    })?;
+    if cave.height() == 0 {
+        return Err(ParseError::new(1, 1, "", "there's no map"));
+    }
+    for (line_no, line) in numbered_lines(input) {
+        let edge_row = line_no == 1 || line_no == cave.height();
+        if !line.starts_with('#')
+            || !line.ends_with('#')
+            || (edge_row && line.contains(|c| c != '#'))
+        {
+            return Err(ParseError::line(line_no, line, "the cave isn't walled in"));
+        }
+    }
+    let teams = factions
+        .iter()
+        .filter(|f| cave.iter().any(|(_, &c)| c == f.symbol));
+    match teams.count() {
+        0 => Err(ParseError::new(1, 1, "", "there are no units")),
+        1 => Err(ParseError::new(1, 1, "", "there's only one faction")),
+        _ => Ok(cave),
+    }
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        Ok(fight(input, &elves_and_goblins(3))?.answer())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (elves_power, outcome) = min_power_for_flawless_victory(input)?
+            .ok_or_else(|| ParseError::fault("the elves can't win without losing anyone"))?;
+        Ok(outcome.answer().with_stat("elves_power", elves_power))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_common::ErrorKind;
+
+    #[test]
+    fn test_example() {
+        let input = include_str!("../input.example");
+        assert_eq!("27730", Day15.part1(input).unwrap().to_string());
+        assert_eq!("4988", Day15.part2(input).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_more_examples() {
+        let examples = [
+            (
+                "#######\n#G..#E#\n#E#E.E#\n#G.##.#\n#...#E#\n#...E.#\n#######",
+                "36334",
+            ),
+            (
+                "#######\n#E..EG#\n#.#G.E#\n#E.##E#\n#G..#.#\n#..E#.#\n#######",
+                "39514",
+            ),
+            (
+                "#######\n#E.G#.#\n#.#G..#\n#G.#.G#\n#G..#.#\n#...E.#\n#######",
+                "27755",
+            ),
+            (
+                "#######\n#.E...#\n#.#..G#\n#.###.#\n#E#G#G#\n#...#G#\n#######",
+                "28944",
+            ),
+            (
+                "#########\n#G......#\n#.E.#...#\n#..##..G#\n#...##..#\n#...#...#\n#.G...G.#\n\
+                 #.....G.#\n#########",
+                "18740",
+            ),
+        ];
+        for (input, outcome) in examples.iter() {
+            assert_eq!(*outcome, Day15.part1(input).unwrap().to_string());
+            // The way paths used to be found plays the same battle.
+            let factions = elves_and_goblins(3);
+            let mut once = Battle::new(input, &factions).unwrap();
+            let mut twice = Battle::new(input, &factions).unwrap();
+            loop {
+                let winners = once.round().unwrap();
+                assert_eq!(winners, twice.round_searching_twice().unwrap());
+                assert_eq!(once.frame(), twice.frame());
+                if winners.is_some() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_power_for_flawless_victory() {
+        let examples = [
+            (include_str!("../input.example"), 15, 29, 172),
+            (
+                "#######\n#E..EG#\n#.#G.E#\n#E.##E#\n#G..#.#\n#..E#.#\n#######",
+                4,
+                33,
+                948,
+            ),
+            (
+                "#######\n#E.G#.#\n#.#G..#\n#G.#.G#\n#G..#.#\n#...E.#\n#######",
+                15,
+                37,
+                94,
+            ),
+            (
+                "#######\n#.E...#\n#.#..G#\n#.###.#\n#E#G#G#\n#...#G#\n#######",
+                12,
+                39,
+                166,
+            ),
+            (
+                "#########\n#G......#\n#.E.#...#\n#..##..G#\n#...##..#\n#...#...#\n#.G...G.#\n\
+                 #.....G.#\n#########",
+                34,
+                30,
+                38,
+            ),
+        ];
+        for &(input, power, rounds, hitpoints) in examples.iter() {
+            let (found, outcome) = min_power_for_flawless_victory(input).unwrap().unwrap();
+            assert_eq!(
+                (power, rounds, vec!['E'], hitpoints),
+                (found, outcome.rounds, outcome.winners, outcome.hitpoints)
+            );
+        }
+
+        // Elves hitting for 17 win without losing anyone, but some harder hitters don't.
+        let open = "############\n#....G.....#\n#.....G.G.G#\n#G..G...E..#\n#...G......#\n\
+                    #........E.#\n#....G.....#\n#..G.......#\n#....E.....#\n#.E.....G..#\n\
+                    #.E..G....E#\n############";
+        let (found, _) = min_power_for_flawless_victory(open).unwrap().unwrap();
+        assert_eq!(17, found);
+
+        // With no elves there's nobody to win.
+        let no_elves = "#####\n#G.G#\n#####";
+        assert!(min_power_for_flawless_victory(no_elves).is_err());
+    }
+
+    #[test]
+    fn test_factions() {
+        let input = "#########\n#E.....G#\n#.......#\n#G..D..E#\n#.......#\n#E.....G#\n#########";
+        let elves = Faction::new('E', 200, 3);
+        let goblins = Faction::new('G', 200, 3);
+        let dwarf = Faction::new('D', 300, 10);
+        let casualties = |d, e, g| -> BTreeMap<char, usize> {
+            vec![('D', d), ('E', e), ('G', g)].into_iter().collect()
+        };
+
+        // Everyone against everyone, and the dwarf is the last one standing.
+        let outcome = fight(input, &[elves.clone(), goblins.clone(), dwarf.clone()]).unwrap();
+        assert_eq!(
+            (76, vec!['D'], 36),
+            (outcome.rounds, outcome.winners, outcome.hitpoints)
+        );
+        assert_eq!(casualties(0, 3, 3), outcome.casualties);
+
+        // The dwarf helps whoever it's allied with, and they win together.
+        let factions = [
+            elves.clone().with_allies(&['D']),
+            goblins.clone(),
+            dwarf.clone(),
+        ];
+        let outcome = fight(input, &factions).unwrap();
+        assert_eq!(
+            (41, vec!['E', 'D'], 658),
+            (outcome.rounds, outcome.winners.clone(), outcome.hitpoints)
+        );
+        assert_eq!(casualties(0, 1, 3), outcome.casualties);
+        assert_eq!(
+            "D 0, E 1, G 3",
+            outcome.answer().stats().last().unwrap().1.to_string()
+        );
+        let outcome = fight(
+            input,
+            &[
+                elves.clone(),
+                goblins.clone().with_allies(&['D']),
+                dwarf.clone(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(vec!['G', 'D'], outcome.winners);
+        assert_eq!(casualties(0, 3, 1), outcome.casualties);
+
+        // Being allied with the dwarf doesn't make the elves and goblins allies, so they fight
+        // while the dwarf waits.
+        let factions = [
+            elves.with_allies(&['D']),
+            goblins.with_allies(&['D']),
+            dwarf,
+        ];
+        let outcome = fight(
+            "#########\n#E.....G#\n#.......#\n#...D...#\n#########",
+            &factions,
+        )
+        .unwrap();
+        assert_eq!(
+            (68, vec!['E', 'D'], 302),
+            (outcome.rounds, outcome.winners, outcome.hitpoints)
+        );
+        assert_eq!(casualties(0, 0, 1), outcome.casualties);
+
+        // With the elves and goblins walled off from each other, the dwarf waits on both sides
+        // and nobody does anything.
+        let err = fight("#########\n#E.D#..G#\n#########", &factions)
+            .err()
+            .unwrap();
+        assert_eq!(
+            "nobody can get at an enemy, so the battle never ends",
+            err.message
+        );
+    }
+
+    #[test]
+    fn test_step_towards() {
+        // The example of picking where to go: the elf heads for (4,2), the nearest of the squares
+        // next to the goblin in reading order, and there are two ways there that start with a
+        // step right or down.
+        let input = "#######\n#.E...#\n#.....#\n#...G.#\n#######";
+        let factions = elves_and_goblins(3);
+        let map = Map::new(&parse_map(input, &factions).unwrap(), &factions);
+        let mut wanted = map.walls.map(|_| false);
+        for &p in &[(4, 2), (3, 3), (5, 3)] {
+            wanted[p] = true;
+        }
+        assert_eq!(Some((3, 1)), map.step_towards((2, 1), &wanted));
+        // Nothing it can get to.
+        let wanted = map.walls.map(|&wall| wall);
+        assert_eq!(None, map.step_towards((2, 1), &wanted));
+    }
+
+    #[test]
+    fn test_bad_map() {
+        let factions = elves_and_goblins(3);
+        let err = parse_map("#####\n#.E.#\n#.X.#\n#####", &factions)
+            .err()
+            .unwrap();
+        assert_eq!((3, 3), (err.line, err.column));
+        let err = parse_map("#####\n#.E.G\n#####", &factions).err().unwrap();
+        assert_eq!(2, err.line);
+        let err = parse_map("#####\n#...#\n#####", &factions).err().unwrap();
+        assert_eq!("there are no units", err.message);
+        let err = parse_map("#####\n#G.G#\n#####", &factions).err().unwrap();
+        assert_eq!("there's only one faction", err.message);
+    }
+
+    #[test]
+    fn test_stalemate() {
+        // Walled off from each other, nobody ever moves or attacks.
+        let input = "#######\n#E.#.G#\n#######";
+        let err = Day15.part1(input).err().unwrap();
+        assert_eq!(ErrorKind::Fault, err.kind);
+        assert!(record_battle(input, 3).is_err());
+        let mut battle = Battle::new(input, &elves_and_goblins(3)).unwrap();
+        assert_eq!(Err(err), battle.round());
+        assert!(battle.frame().stalemate);
+    }
+}
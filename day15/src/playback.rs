@@ -0,0 +1,360 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Watching a battle in the terminal, redrawn in place a round at a time.
+//!
+//! Each team has its own colour, and every unit has a bar for its hitpoints next to the row it's
+//! on.  The terminal is left in line mode, so the controls aren't single keypresses: each is a
+//! command typed on a line of its own and sent with Enter.
+//!
+//! - An empty line steps a round forward, and `b` steps one back.  Either pauses it.
+//! - `p` plays or pauses.
+//! - `+` and `-` play faster or slower.
+//! - `q` quits.
+
+use crate::Battle;
+use aoc_common::grid::{Grid, Point};
+use std::{
+    io::{self, Write},
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+/// A unit in a frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameUnit {
+    pub team: char,
+    pub pos: Point,
+    pub hitpoints: i64,
+    /// What it started with.
+    pub max_hitpoints: i64,
+}
+
+/// Where everything is after a round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// Rounds played, counting the one the battle ended in.
+    pub round: usize,
+    pub walls: Grid<bool>,
+    /// The factions, which pick their colours.
+    pub teams: Vec<char>,
+    /// The units still alive.
+    pub units: Vec<FrameUnit>,
+    /// Who won, once the battle's over.
+    pub winners: Option<Vec<char>>,
+    /// Whether nobody did anything in the round, so the battle never ends.
+    pub stalemate: bool,
+}
+
+/// The colours the teams get, in order: green for elves and red for goblins.
+const PALETTE: [&str; 6] = ["32", "31", "33", "34", "35", "36"];
+
+/// How many squares wide a hitpoint bar is.
+const BAR_WIDTH: i64 = 10;
+
+/// A bar as full as `hitpoints` is of `max`, rounding up so a unit that's alive shows.
+fn hitpoint_bar(hitpoints: i64, max: i64) -> String {
+    let filled = ((hitpoints * BAR_WIDTH + max - 1) / max).clamp(0, BAR_WIDTH);
+    format!(
+        "{}{}",
+        "█".repeat(filled as usize),
+        "░".repeat((BAR_WIDTH - filled) as usize)
+    )
+}
+
+impl Frame {
+    fn colour(&self, team: char) -> &'static str {
+        let at = self.teams.iter().position(|&t| t == team).unwrap_or(0);
+        PALETTE[at % PALETTE.len()]
+    }
+
+    /// The cave in colour, with the units on each row and their hitpoints after it.
+    pub fn draw(&self) -> String {
+        let mut text = String::new();
+        for y in self.walls.min().1..=self.walls.max().1 {
+            let mut bars = Vec::new();
+            for x in self.walls.min().0..=self.walls.max().0 {
+                let unit = self.units.iter().find(|u| u.pos == (x, y));
+                match unit {
+                    Some(unit) => {
+                        let colour = self.colour(unit.team);
+                        text.push_str(&format!("\x1b[1;{}m{}\x1b[0m", colour, unit.team));
+                        bars.push(format!(
+                            "\x1b[{}m{} {}\x1b[0m {:>3}",
+                            colour,
+                            unit.team,
+                            hitpoint_bar(unit.hitpoints, unit.max_hitpoints),
+                            unit.hitpoints
+                        ));
+                    }
+                    None if self.walls[(x, y)] => text.push_str("\x1b[90m#\x1b[0m"),
+                    None => text.push('.'),
+                }
+            }
+            if !bars.is_empty() {
+                text.push_str("   ");
+                text.push_str(&bars.join("  "));
+            }
+            text.push_str("\x1b[K\n");
+        }
+        text
+    }
+}
+
+/// What to do from the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Step,
+    Back,
+    /// Play if it's paused, or pause if it's playing.
+    Toggle,
+    Faster,
+    Slower,
+    Quit,
+}
+
+impl Command {
+    /// The command for a line of input, if it is one.
+    pub fn parse(line: &str) -> Option<Command> {
+        match line.trim() {
+            "" => Some(Command::Step),
+            "b" => Some(Command::Back),
+            "p" => Some(Command::Toggle),
+            "+" | "=" => Some(Command::Faster),
+            "-" => Some(Command::Slower),
+            "q" => Some(Command::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Rounds a second it can play at.
+const SPEEDS: [u64; 6] = [1, 2, 4, 8, 16, 32];
+
+/// A battle being played back, with the frames so far so it can go back to them.
+pub struct Player {
+    battle: Battle,
+    frames: Vec<Frame>,
+    at: usize,
+    playing: bool,
+    speed: usize,
+}
+
+impl Player {
+    /// Starts playing `battle` from where it is, at four rounds a second.
+    pub fn new(battle: Battle) -> Player {
+        Player {
+            frames: vec![battle.frame()],
+            battle,
+            at: 0,
+            playing: true,
+            speed: 2,
+        }
+    }
+
+    /// The frame it's showing.
+    pub fn frame(&self) -> &Frame {
+        &self.frames[self.at]
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// How long to show each frame for when it's playing.
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(1000 / SPEEDS[self.speed])
+    }
+
+    /// Moves on a round, playing it if it hasn't been yet.  Returns false if the battle's over.
+    fn forward(&mut self) -> bool {
+        if self.at + 1 == self.frames.len() {
+            let frame = &self.frames[self.at];
+            if frame.winners.is_some() || frame.stalemate {
+                return false;
+            }
+            // A battle that never ends shows as a stalemate in the frame.
+            let _ = self.battle.round();
+            self.frames.push(self.battle.frame());
+        }
+        self.at += 1;
+        true
+    }
+
+    /// Moves on a round if it's playing, and stops at the end.
+    pub fn tick(&mut self) {
+        if self.playing && !self.forward() {
+            self.playing = false;
+        }
+    }
+
+    /// Does `command`.  Returns false to quit.
+    pub fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::Step => {
+                self.playing = false;
+                self.forward();
+            }
+            Command::Back => {
+                self.playing = false;
+                self.at = self.at.saturating_sub(1);
+            }
+            Command::Toggle => self.playing = !self.playing,
+            Command::Faster => self.speed = (self.speed + 1).min(SPEEDS.len() - 1),
+            Command::Slower => self.speed = self.speed.saturating_sub(1),
+            Command::Quit => return false,
+        }
+        true
+    }
+
+    /// The whole screen: where the battle's got to, the cave, and the controls.  It starts at
+    /// the top left and clears whatever it doesn't draw over.
+    pub fn render(&self) -> String {
+        let frame = self.frame();
+        let status = match &frame.winners {
+            Some(winners) => {
+                let winners: Vec<String> = winners.iter().map(|w| w.to_string()).collect();
+                format!("{} won", winners.join(" and "))
+            }
+            None if frame.stalemate => "nobody can get at an enemy, so it never ends".to_string(),
+            None if self.playing => format!("playing at {} rounds a second", SPEEDS[self.speed]),
+            None => "paused".to_string(),
+        };
+        format!(
+            "\x1b[HRound {}: {}\x1b[K\n{}Type a command and press Enter: nothing to step, b back, \
+             p play/pause, + or - speed, q quit\x1b[K\n\x1b[J",
+            frame.round,
+            status,
+            frame.draw()
+        )
+    }
+}
+
+/// Plays `battle` back to `out`, doing the commands as they come in.  It stops when told to
+/// quit, or at the end of the battle once the commands stop coming.
+pub fn play<W: Write>(battle: Battle, commands: Receiver<Command>, out: &mut W) -> io::Result<()> {
+    let mut player = Player::new(battle);
+    let mut listening = true;
+    write!(out, "\x1b[2J")?;
+    loop {
+        write!(out, "{}", player.render())?;
+        out.flush()?;
+        let command = if player.is_playing() {
+            if listening {
+                match commands.recv_timeout(player.delay()) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        listening = false;
+                        None
+                    }
+                }
+            } else {
+                thread::sleep(player.delay());
+                None
+            }
+        } else if listening {
+            match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => return Ok(()),
+            }
+        } else {
+            return Ok(());
+        };
+        match command {
+            Some(command) => {
+                if !player.apply(command) {
+                    return Ok(());
+                }
+            }
+            None => player.tick(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Faction;
+    use std::sync::mpsc;
+
+    fn battle() -> Battle {
+        let factions = [Faction::new('E', 200, 3), Faction::new('G', 200, 3)];
+        Battle::new(include_str!("../input.example"), &factions).unwrap()
+    }
+
+    #[test]
+    fn test_hitpoint_bar() {
+        assert_eq!("██████████", hitpoint_bar(200, 200));
+        assert_eq!("█████░░░░░", hitpoint_bar(100, 200));
+        assert_eq!("█░░░░░░░░░", hitpoint_bar(2, 200));
+        assert_eq!("░░░░░░░░░░", hitpoint_bar(0, 200));
+    }
+
+    #[test]
+    fn test_draw() {
+        let drawn = battle().frame().draw();
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(7, lines.len());
+        // The elf and goblin on the third row, in green and red.
+        assert!(lines[2].contains("\x1b[1;32mE\x1b[0m\x1b[1;31mG\x1b[0m"));
+        assert!(lines[2].contains("\x1b[32mE ██████████\x1b[0m 200"));
+    }
+
+    #[test]
+    fn test_player() {
+        let mut player = Player::new(battle());
+        assert!(player.is_playing());
+        player.tick();
+        assert_eq!(1, player.frame().round);
+        assert!(player.apply(Command::Step));
+        assert!(!player.is_playing());
+        assert_eq!(2, player.frame().round);
+        player.apply(Command::Back);
+        player.apply(Command::Back);
+        player.apply(Command::Back);
+        assert_eq!(0, player.frame().round);
+        // Going forward again shows the rounds it's already played.
+        player.apply(Command::Step);
+        assert_eq!(1, player.frame().round);
+        assert_eq!(3, player.frames.len());
+
+        assert_eq!(Duration::from_millis(250), player.delay());
+        player.apply(Command::Faster);
+        assert_eq!(Duration::from_millis(125), player.delay());
+        for _ in 0..10 {
+            player.apply(Command::Slower);
+        }
+        assert_eq!(Duration::from_secs(1), player.delay());
+        assert!(player.render().contains("Round 1: paused"));
+
+        // Play to the end, where it stops by itself.
+        player.apply(Command::Toggle);
+        while player.is_playing() {
+            player.tick();
+        }
+        assert_eq!(48, player.frame().round);
+        assert!(player.render().contains("Round 48: G won"));
+        assert!(!player.apply(Command::Quit));
+    }
+
+    #[test]
+    fn test_play() {
+        let (send, commands) = mpsc::channel();
+        for line in &["", "", "x", "b", "q"] {
+            if let Some(command) = Command::parse(line) {
+                send.send(command).unwrap();
+            }
+        }
+        let mut out = Vec::new();
+        play(battle(), commands, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let rounds: Vec<&str> = out
+            .split("\x1b[HRound ")
+            .skip(1)
+            .map(|screen| screen.split(':').next().unwrap())
+            .collect();
+        assert_eq!(vec!["0", "1", "2", "1"], rounds);
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Recording what every unit does in a battle, and checking a battle still goes the same way.
+//!
+//! A log has a `round N` line at the start of each round, counting from 1, and then a line for
+//! each unit's turn in the order they take them:
+//!
+//! ```text
+//! round 1
+//! G0 (2,1) -> (3,1)
+//! E1 (4,2) -> (4,2) hits G2 for 3
+//! ```
+//!
+//! That's the unit (its team, and which unit it was counting in reading order at the start),
+//! where it started and ended its turn, and who it hit and how hard if it attacked.  The last
+//! round is the one a unit found no enemies left in, and stops at that unit.  Blank lines are
+//! skipped.
+
+use crate::{elves_and_goblins, parse_map, Map};
+use aoc_common::{grid::Point, numbered_lines, parse_number, ParseError};
+use std::fmt;
+
+/// A unit hitting another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Attack {
+    pub unit: usize,
+    pub team: char,
+    pub damage: i64,
+}
+
+/// What a unit did on its turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Turn {
+    pub unit: usize,
+    pub team: char,
+    pub from: Point,
+    pub to: Point,
+    pub attack: Option<Attack>,
+}
+
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{} ({},{}) -> ({},{})",
+            self.team, self.unit, self.from.0, self.from.1, self.to.0, self.to.1
+        )?;
+        if let Some(attack) = &self.attack {
+            write!(
+                f,
+                " hits {}{} for {}",
+                attack.team, attack.unit, attack.damage
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a battle went differently to its log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The round, counting from 1.
+    pub round: usize,
+    /// The turn in the round, counting from 1.
+    pub turn: usize,
+    /// The turn in the log, or None if the log's round ended first.
+    pub logged: Option<Turn>,
+    /// The turn in the battle, or None if the battle's round ended first.
+    pub played: Option<Turn>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let describe = |turn: &Option<Turn>| match turn {
+            Some(turn) => turn.to_string(),
+            None => "nothing".to_string(),
+        };
+        write!(
+            f,
+            "round {}, turn {}: the log has {}, but the battle has {}",
+            self.round,
+            self.turn,
+            describe(&self.logged),
+            describe(&self.played)
+        )
+    }
+}
+
+/// Parses a unit like `E12`.
+fn parse_unit(line_no: usize, line: &str, text: &str) -> Result<(char, usize), ParseError> {
+    match text.chars().next() {
+        Some(team @ 'E') | Some(team @ 'G') => Ok((team, parse_number(line_no, line, &text[1..])?)),
+        _ => Err(ParseError::at(line_no, line, text, "not a unit")),
+    }
+}
+
+/// Parses a point like `(3,4)`.
+fn parse_point(line_no: usize, line: &str, text: &str) -> Result<Point, ParseError> {
+    let inside = text
+        .strip_prefix('(')
+        .and_then(|t| t.strip_suffix(')'))
+        .ok_or_else(|| ParseError::at(line_no, line, text, "not a point"))?;
+    let (x, y) = inside
+        .split_once(',')
+        .ok_or_else(|| ParseError::at(line_no, line, text, "not a point"))?;
+    Ok((
+        parse_number(line_no, line, x)?,
+        parse_number(line_no, line, y)?,
+    ))
+}
+
+fn parse_turn(line_no: usize, line: &str) -> Result<Turn, ParseError> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (unit, from, to, attack) = match words.as_slice() {
+        [unit, from, "->", to] => (unit, from, to, None),
+        [unit, from, "->", to, "hits", target, "for", damage] => {
+            let (team, target) = parse_unit(line_no, line, target)?;
+            let attack = Attack {
+                unit: target,
+                team,
+                damage: parse_number(line_no, line, damage)?,
+            };
+            (unit, from, to, Some(attack))
+        }
+        _ => {
+            let message = "expected a unit, where it moved and who it hit";
+            return Err(ParseError::line(line_no, line, message));
+        }
+    };
+    let (team, unit) = parse_unit(line_no, line, unit)?;
+    Ok(Turn {
+        unit,
+        team,
+        from: parse_point(line_no, line, from)?,
+        to: parse_point(line_no, line, to)?,
+        attack,
+    })
+}
+
+/// Reads a log into the turns of each round.
+fn parse_log(log: &str) -> Result<Vec<Vec<Turn>>, ParseError> {
+    let mut rounds: Vec<Vec<Turn>> = Vec::new();
+    for (line_no, line) in numbered_lines(log) {
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [] => {}
+            ["round", number] => {
+                let number: usize = parse_number(line_no, line, number)?;
+                if number != rounds.len() + 1 {
+                    let message = format!("expected round {}", rounds.len() + 1);
+                    return Err(ParseError::line(line_no, line, &message));
+                }
+                rounds.push(Vec::new());
+            }
+            _ => match rounds.last_mut() {
+                Some(turns) => turns.push(parse_turn(line_no, line)?),
+                None => return Err(ParseError::line(line_no, line, "expected round 1")),
+            },
+        }
+    }
+    Ok(rounds)
+}
+
+/// Plays the battle in `input` with the elves hitting for `elves_power`, and writes down every
+/// turn.
+pub fn record_battle(input: &str, elves_power: i64) -> Result<String, ParseError> {
+    let factions = elves_and_goblins(elves_power);
+    let mut map = Map::new(&parse_map(input, &factions)?, &factions);
+    let mut log = String::new();
+    for round in 1.. {
+        let mut turns = Vec::new();
+        let winner = map.round(&mut turns)?;
+        log.push_str(&format!("round {}\n", round));
+        for turn in turns {
+            log.push_str(&format!("{}\n", turn));
+        }
+        if winner.is_some() {
+            break;
+        }
+    }
+    Ok(log)
+}
+
+/// Plays the battle in `input` alongside `log`, and returns the first turn where it does
+/// something else, if there is one.  A log that stops early only checks the rounds it has.
+pub fn verify_battle(
+    input: &str,
+    elves_power: i64,
+    log: &str,
+) -> Result<Option<Divergence>, ParseError> {
+    let factions = elves_and_goblins(elves_power);
+    let mut map = Map::new(&parse_map(input, &factions)?, &factions);
+    let mut over = false;
+    for (round, logged) in parse_log(log)?.into_iter().enumerate() {
+        let mut played = Vec::new();
+        if !over {
+            over = map.round(&mut played)?.is_some();
+        }
+        for turn in 0..logged.len().max(played.len()) {
+            let (logged, played) = (logged.get(turn).copied(), played.get(turn).copied());
+            if logged != played {
+                return Ok(Some(Divergence {
+                    round: round + 1,
+                    turn: turn + 1,
+                    logged,
+                    played,
+                }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_movement() {
+        // The movement example, where nobody's in range until the third round.
+        let input = include_str!("../input.mvmt");
+        let log = record_battle(input, 3).unwrap();
+        let third: Vec<&str> = log
+            .split("round ")
+            .nth(3)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .collect();
+        assert_eq!("G0 (3,1) -> (3,2)", third[0]);
+        assert_eq!("E4 (4,3) -> (4,3) hits G1 for 3", third[4]);
+
+        let factions = elves_and_goblins(3);
+        let mut map = Map::new(&parse_map(input, &factions).unwrap(), &factions);
+        for _ in 0..3 {
+            map.round(&mut Vec::new()).unwrap();
+        }
+        let after: String = map
+            .to_string()
+            .lines()
+            .map(|line| format!("{}\n", line.split(' ').next().unwrap()))
+            .collect();
+        assert_eq!(
+            "#########\n#.......#\n#..GGG..#\n#..GEG..#\n#G..G...#\n#......G#\n#.......#\n\
+             #.......#\n#########\n",
+            after
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let input = include_str!("../input.example");
+        let log = include_str!("../input.example.log");
+        assert_eq!(log, record_battle(input, 3).unwrap());
+        assert_eq!(None, verify_battle(input, 3, log).unwrap());
+        // The first few rounds are fine on their own.
+        let start = log.split("round 3\n").next().unwrap();
+        assert_eq!(None, verify_battle(input, 3, start).unwrap());
+
+        // Elves that hit harder do the same until their first hit.
+        let divergence = verify_battle(input, 4, log).unwrap().unwrap();
+        assert_eq!((1, 2), (divergence.round, divergence.turn));
+        assert_eq!(
+            "round 1, turn 2: the log has E1 (4,2) -> (4,2) hits G2 for 3, but the battle has \
+             E1 (4,2) -> (4,2) hits G2 for 4",
+            divergence.to_string()
+        );
+
+        // A log that goes on after the battle ended in round 48.
+        let longer = format!("{}round 49\nG0 (1,1) -> (1,1)\n", log);
+        let divergence = verify_battle(input, 3, &longer).unwrap().unwrap();
+        assert_eq!(
+            (49, 1, None),
+            (divergence.round, divergence.turn, divergence.played)
+        );
+    }
+
+    #[test]
+    fn test_bad_log() {
+        let input = include_str!("../input.example");
+        let err = verify_battle(input, 3, "G0 (1,1) -> (1,1)").unwrap_err();
+        assert_eq!(
+            "1:1: expected round 1: \"G0 (1,1) -> (1,1)\"",
+            err.to_string()
+        );
+        let err = verify_battle(input, 3, "round 1\nround 3").unwrap_err();
+        assert_eq!(2, err.line);
+        let err = verify_battle(input, 3, "round 1\nX0 (1,1) -> (1,1)").unwrap_err();
+        assert_eq!("2:1: not a unit: \"X0\"", err.to_string());
+        let err = verify_battle(input, 3, "round 1\nG0 (1,1) -> 1,1").unwrap_err();
+        assert_eq!("2:13: not a point: \"1,1\"", err.to_string());
+        let err = verify_battle(input, 3, "round 1\nG0 (1,1) -> (1,1) hits").unwrap_err();
+        assert_eq!(2, err.line);
+    }
+}
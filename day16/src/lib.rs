@@ -0,0 +1,211 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, Answer, ParseError, Solution};
+use elfcode::{Fault, Instruction, Machine, Opcode, OpcodeSolver, Overflow, Program};
+use log::info;
+use std::collections::BTreeMap;
+
+struct Sample([u16; 4], [u8; 4], [u16; 4]);
+
+impl Sample {
+    fn new(before: &[u16], instruction: &[u8], after: &[u16]) -> Sample {
+        assert!(before.len() == 4, "Before should be 4 numbers not {:?}", before);
+        assert!(instruction.len() == 4, "Instruction should be 4 numbers not {:?}", instruction);
+        assert!(after.len() == 4, "After should be 4 numbers not {:?}", after);
+        let mut before_ar = [0; 4];
+        before_ar.copy_from_slice(&before[..4]);
+        let mut inst_ar = [0; 4];
+        inst_ar.copy_from_slice(&instruction[..4]);
+        let mut after_ar = [0; 4];
+        after_ar.copy_from_slice(&after[..4]);
+        Sample(before_ar, inst_ar, after_ar)
+    }
+
+    /// Dowse the opcodes this sample could have.  An opcode that can't run on the sample, because
+    /// it overflows or one of the operands would be a register that isn't there, isn't it.
+    fn dowse_instructions(&self) -> Vec<Opcode> {
+        let coded = &self.1;
+        let (a, b, c) = (coded[1].into(), coded[2].into(), coded[3].into());
+        Opcode::ALL
+            .iter()
+            .filter(|opcode| {
+                let mut registers = self.0;
+                let ran = opcode.execute(&mut registers, a, b, c, Overflow::Trapping);
+                ran.is_ok() && registers == self.2
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The opcode value that is given in the instruction
+    fn opcode_val(&self) -> u8 {
+        self.1[0]
+    }
+}
+
+/// Parses an instruction like "9 2 1 2".  There are only 16 opcodes and 4 registers to put the
+/// result in.
+fn parse_instruction(line_no: usize, line: &str) -> Result<Vec<u8>, ParseError> {
+    let instruction: Vec<u8> = parse_numbers(line_no, line, 4)?;
+    if instruction[0] >= 16 {
+        return Err(ParseError::line(line_no, line, "there are only 16 opcodes"));
+    }
+    if instruction[3] >= 4 {
+        return Err(ParseError::line(line_no, line, "there are only 4 registers"));
+    }
+    Ok(instruction)
+}
+
+/// Parses the registers from a line like "Before: [3, 2, 1, 1]".
+fn parse_registers(line_no: usize, line: &str, label: &str) -> Result<Vec<u16>, ParseError> {
+    if !line.starts_with(label) {
+        let message = format!("expected \"{}\" and the registers", label);
+        return Err(ParseError::line(line_no, line, &message));
+    }
+    parse_numbers(line_no, line, 4)
+}
+
+/// Parses the samples, and then the test program, which comes after them.
+fn parse_input(input: &str) -> Result<(Vec<Sample>, Vec<Vec<u8>>), ParseError> {
+    let mut lines = numbered_lines(input).filter(|(_, l)| !l.trim().is_empty()).peekable();
+    let mut samples = Vec::new();
+    while let Some(&(line_no, line)) = lines.peek() {
+        if !line.starts_with("Before:") {
+            break;
+        }
+        lines.next();
+        let before = parse_registers(line_no, line, "Before:")?;
+        let (line_no, line) = lines.next().unwrap_or((line_no + 1, ""));
+        let instruction = parse_instruction(line_no, line)?;
+        let (line_no, line) = lines.next().unwrap_or((line_no + 1, ""));
+        let after = parse_registers(line_no, line, "After:")?;
+        samples.push(Sample::new(&before, &instruction, &after));
+    }
+    let program = lines
+        .map(|(line_no, line)| parse_instruction(line_no, line))
+        .collect::<Result<_, ParseError>>()?;
+    Ok((samples, program))
+}
+
+/// Dowses all the samples.  Returns how many samples could represent three or more opcodes, and
+/// a solver with all of them in it to work out which opcode each opcode value is.
+fn dowse_opcodes(samples: &[Sample]) -> (usize, OpcodeSolver) {
+    let mut more_than_three = 0;
+    let mut solver = OpcodeSolver::new();
+    for sample in samples {
+        let possible = sample.dowse_instructions();
+        if possible.len() >= 3 {
+            more_than_three += 1;
+        }
+        solver.add_sample(sample.opcode_val(), &possible);
+    }
+    (more_than_three, solver)
+}
+
+/// Works out the opcodes from the samples, and checks the test program only uses ones there are
+/// samples of.
+fn solve_opcodes(
+    solver: &OpcodeSolver,
+    program: &[Vec<u8>],
+) -> Result<BTreeMap<u8, Opcode>, ParseError> {
+    let opcodes = solver.solve().map_err(|e| ParseError::fault(&e.to_string()))?;
+    if let Some(instr) = program.iter().find(|instr| !opcodes.contains_key(&instr[0])) {
+        let message = format!("there are no samples of opcode {}", instr[0]);
+        return Err(ParseError::fault(&message));
+    }
+    Ok(opcodes)
+}
+
+/// Executes the test program, with the opcodes that were determined.
+fn run_program(opcodes: &BTreeMap<u8, Opcode>, program: &[Vec<u8>]) -> Result<[u16; 4], Fault> {
+    let instructions = program
+        .iter()
+        .map(|instr| {
+            let opcode = opcodes[&instr[0]];
+            Instruction::new(opcode, instr[1].into(), instr[2].into(), instr[3].into())
+        })
+        .collect();
+    let mut machine = Machine::new(Program::new(None, instructions))?;
+    machine.run()?;
+    Ok(*machine.registers())
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let (samples, _) = parse_input(input)?;
+        info!("{} samples loaded", samples.len());
+        let (more_than_three, _) = dowse_opcodes(&samples);
+        Ok(Answer::new(more_than_three))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let (samples, program) = parse_input(input)?;
+        let (_, solver) = dowse_opcodes(&samples);
+        let opcodes = solve_opcodes(&solver, &program)?;
+        info!("We determined these opcodes: {:?}", opcodes);
+        let registers = run_program(&opcodes, &program)
+            .map_err(|fault| ParseError::fault(&fault.to_string()))?;
+        info!("{:?} at the end", registers);
+        Ok(Answer::new(registers[0]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let input = "Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [3, 2, 2, 1]
+
+
+
+9 2 1 2
+";
+        let (samples, program) = parse_input(input).unwrap();
+        assert_eq!(1, samples.len());
+        assert_eq!(vec![vec![9, 2, 1, 2]], program);
+        let possible = samples[0].dowse_instructions();
+        assert_eq!(3, possible.len());
+        assert!(possible.contains(&Opcode::mulr));
+        assert!(possible.contains(&Opcode::addi));
+        assert!(possible.contains(&Opcode::seti));
+
+        let err = parse_input("Before: [3, 2, 1, 1]\n9 2 1 2\n").err().unwrap();
+        assert_eq!(3, err.line);
+        let err = parse_input("Before: [3, 2, 1, 1]\n16 2 1 2\n").err().unwrap();
+        assert_eq!(2, err.line);
+    }
+
+    #[test]
+    fn test_solve_opcodes() {
+        let input = "Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [3, 2, 2, 1]
+
+Before: [3, 2, 1, 1]
+4 2 1 2
+After:  [3, 2, 1, 1]
+
+Before: [0, 0, 0, 0]
+9 0 0 0
+After:  [1, 0, 0, 0]
+";
+        let without_last = &input[..input.find("\nBefore: [0").unwrap()];
+        let (samples, program) = parse_input(without_last).unwrap();
+        let (more_than_three, solver) = dowse_opcodes(&samples);
+        assert_eq!(2, more_than_three);
+        let err = solve_opcodes(&solver, &program).unwrap_err();
+        assert!(err.message.starts_with("the samples don't pin down every opcode: 4 could be"));
+
+        let (samples, program) = parse_input(input).unwrap();
+        let (_, solver) = dowse_opcodes(&samples);
+        let err = solve_opcodes(&solver, &program).unwrap_err();
+        assert_eq!("no opcode does what the samples for 9 did", err.message);
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{
+    grid::{Grid, Point},
+    numbered_lines, parse_numbers, Answer, ParseError, Solution,
+};
+use log::{debug, info, trace};
+use std::{collections::HashSet, fmt};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Ground {
+    Sand,
+    Clay,
+    /// Sand that water has touched.
+    Flowing,
+    /// Water that has settled.
+    Settled,
+}
+
+impl fmt::Display for Ground {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = match self {
+            Ground::Sand => '.',
+            Ground::Clay => '#',
+            Ground::Flowing => '|',
+            Ground::Settled => '~',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+enum FlowSituation {
+    /// There is a corner here (wall to the left or right and a wall to the bottom)
+    Corner(Point),
+    /// There is a cliff, and the water would fall starting here
+    Cliff(Point),
+}
+
+/// A vein of clay, from one x to another and one y to another.
+type Vein = ((i64, i64), (i64, i64));
+
+struct ClayScan {
+    ground: Grid<Ground>,
+    /// locations we've dropped water from
+    drops: HashSet<Point>,
+    /// The topmost and bottommost clay.
+    range_y: (i64, i64),
+}
+
+impl ClayScan {
+    /// A scan of the ground with clay in `veins`.  The scan goes from the top down to the bottommost clay, and one column past the clay
+    /// on each side for water to fall down.
+    fn new(veins: &[Vein]) -> ClayScan {
+        let min_x = veins.iter().map(|(x, _)| x.0).min().unwrap_or(0) - 1;
+        let max_x = veins.iter().map(|(x, _)| x.1).max().unwrap_or(0) + 1;
+        let min_y = veins.iter().map(|(_, y)| y.0).min().unwrap_or(0);
+        let max_y = veins.iter().map(|(_, y)| y.1).max().unwrap_or(0);
+        let width = (max_x - min_x + 1) as usize;
+        let mut ground = Grid::new((min_x, 0), width, max_y as usize + 1, Ground::Sand);
+        for &((x1, x2), (y1, y2)) in veins {
+            for y in y1..=y2 {
+                for x in x1..=x2 {
+                    ground[(x, y)] = Ground::Clay;
+                }
+            }
+        }
+        ClayScan {
+            ground,
+            drops: HashSet::new(),
+            range_y: (min_y, max_y),
+        }
+    }
+
+    // Returns true if there is either water or clay at p.
+    fn spot_full(&self, p: Point) -> bool {
+        matches!(
+            self.ground.get(p),
+            Some(Ground::Clay) | Some(Ground::Settled)
+        )
+    }
+
+    /// Water has touched p.
+    fn reach(&mut self, p: Point) {
+        if let Some(ground) = self.ground.get_mut(p) {
+            if *ground == Ground::Sand {
+                *ground = Ground::Flowing;
+            }
+        }
+    }
+
+    fn situation_left_of(&mut self, (x, y): Point) -> FlowSituation {
+        let mut scan_x = x;
+        loop {
+            // We weached this spot with water no matter what.
+            self.reach((scan_x, y));
+            // If we don't have something below us, we're cliffed
+            if !self.spot_full((scan_x, y + 1)) {
+                return FlowSituation::Cliff((scan_x, y));
+            }
+            // If we have something to our left, we are cornered
+            if self.spot_full((scan_x - 1, y)) {
+                return FlowSituation::Corner((scan_x, y));
+            }
+            // Otherwise let's keep scanning.
+            scan_x -= 1;
+        }
+    }
+
+    fn situation_right_of(&mut self, (x, y): Point) -> FlowSituation {
+        let mut scan_x = x;
+        loop {
+            // We reached this spot with water.
+            self.reach((scan_x, y));
+            // If we don't have something below us, we're cliffed
+            if !self.spot_full((scan_x, y + 1)) {
+                return FlowSituation::Cliff((scan_x, y));
+            }
+            // If we have something to our right, we are cornered
+            if self.spot_full((scan_x + 1, y)) {
+                return FlowSituation::Corner((scan_x, y));
+            }
+            // Otherwise let's keep scanning.
+            scan_x += 1;
+        }
+    }
+
+    /// Fills the clay if water is falling starting at `start`
+    fn fill(&mut self, start: Point) {
+        let mut drops_left = HashSet::new();
+        let mut dead_drops = HashSet::new();
+        drops_left.insert(start);
+        'drops: while !drops_left.is_empty() {
+            let drop_to_take = *drops_left.iter().next().unwrap();
+            let drop_start = drops_left.take(&drop_to_take).unwrap();
+            self.drops.insert(drop_start);
+            let mut drop_loc = drop_start;
+            trace!("Water source location at {:?}", drop_loc);
+            // If we are already underwater, we can ignore this.
+            if self.ground.get(drop_loc) == Some(&Ground::Settled) {
+                trace!("Underwater drop location at {:?}, bubbling up...", drop_loc);
+                drop_loc = (drop_loc.0, drop_loc.1 - 1);
+                drops_left.insert(drop_loc);
+                trace!("\n{}", self.format_rows(drop_loc.1 - 20, drop_loc.1 + 5));
+                continue 'drops;
+            }
+            // Travel down until you hit something
+            while !self.spot_full((drop_loc.0, drop_loc.1 + 1)) {
+                drop_loc = (drop_loc.0, drop_loc.1 + 1);
+                // If we are still falling off the map, we can't fill anything else.
+                if drop_loc.1 > self.range_y.1 {
+                    debug!(
+                        "Water flowed off the map at {:?}, {} drops left",
+                        drop_loc,
+                        drops_left.len()
+                    );
+                    dead_drops.insert(drop_start);
+                    continue 'drops;
+                }
+                // Otherwise we've "reached" this tile.
+                self.reach(drop_loc);
+            }
+            // What's going on here?
+            match (
+                self.situation_left_of(drop_loc),
+                self.situation_right_of(drop_loc),
+            ) {
+                (FlowSituation::Corner((x1, y1)), FlowSituation::Corner((x2, _))) => {
+                    // We are gonna fill this whole level from x1 to x2, then fill again,
+                    // with the next drop from where we started.
+                    trace!("Filling with water ({}, {}..{})", y1, x1, x2);
+                    for x in x1..x2 + 1 {
+                        self.ground[(x, y1)] = Ground::Settled;
+                    }
+                    drops_left.insert(drop_start);
+                    trace!("\n{}", self.format_rows(drop_start.1, y1 + 5));
+                }
+                (FlowSituation::Corner(_), FlowSituation::Cliff(right)) => {
+                    // We fall off the right side. Add a drop location there, and we're done.
+                    if !dead_drops.contains(&right) {
+                        drops_left.insert(right);
+                    }
+                    trace!("Flowed off to the right {:?}", right);
+                    trace!("\n{}", self.format_rows(drop_start.1, right.1 + 5));
+                }
+                (FlowSituation::Cliff(left), FlowSituation::Corner(_)) => {
+                    // Same as last time, but this time it's the left side.
+                    if !dead_drops.contains(&left) {
+                        drops_left.insert(left);
+                    }
+                    trace!("Flowed off to the left {:?}", left);
+                    trace!("\n{}", self.format_rows(drop_start.1, left.1 + 5));
+                }
+                (FlowSituation::Cliff(left), FlowSituation::Cliff(right)) => {
+                    // We are overflowing both sides, so we drop from both sides at once.
+                    if dead_drops.contains(&left) && dead_drops.contains(&right) {
+                        // The drop that made this is also a dead drop,
+                        dead_drops.insert(drop_start);
+                    } else {
+                        if !dead_drops.contains(&left) {
+                            drops_left.insert(left);
+                        }
+                        if !dead_drops.contains(&right) {
+                            drops_left.insert(right);
+                        }
+                    }
+                    trace!("Flowed off both sides {:?} and {:?}", left, right);
+                    trace!("\n{}", self.format_rows(drop_start.1, left.1 + 5));
+                }
+            }
+        }
+    }
+
+    /// Draws the rows of the scan from `top` to `bottom`.
+    fn format_rows(&self, top: i64, bottom: i64) -> String {
+        let mut s = String::new();
+        for y in top.max(0)..=bottom.min(self.ground.max().1) {
+            for x in self.ground.min().0..=self.ground.max().0 {
+                if self.drops.contains(&(x, y)) {
+                    s.push('*');
+                } else {
+                    s += &self.ground[(x, y)].to_string();
+                }
+            }
+            s.push_str("\r\n");
+        }
+        s
+    }
+}
+
+impl fmt::Display for ClayScan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format_rows(0, self.range_y.1))
+    }
+}
+
+/// Parses the clay veins, like "x=495, y=2..7" or "y=7, x=495..501".
+fn parse_scan(input: &str) -> Result<ClayScan, ParseError> {
+    let mut veins = Vec::new();
+
+    for (line_no, line) in numbered_lines(input) {
+        let nums: Vec<i64> = parse_numbers(line_no, line, 3)?;
+        if nums[1] > nums[2] {
+            return Err(ParseError::line(line_no, line, "the range is backwards"));
+        }
+        if line.starts_with("x=") && line.contains(", y=") {
+            veins.push(((nums[0], nums[0]), (nums[1], nums[2])));
+        } else if line.starts_with("y=") && line.contains(", x=") {
+            veins.push(((nums[1], nums[2]), (nums[0], nums[0])));
+        } else {
+            return Err(ParseError::line(
+                line_no,
+                line,
+                "expected a vein like \"x=495, y=2..7\"",
+            ));
+        }
+    }
+    let mut scan = ClayScan::new(&veins);
+
+    info!(
+        "Found {} clay locations in the ranges: x {:?} y {:?}",
+        scan.ground
+            .iter()
+            .filter(|(_, &g)| g == Ground::Clay)
+            .count(),
+        (scan.ground.min().0, scan.ground.max().0),
+        scan.range_y
+    );
+
+    info!("Filling starting at y = 0, x = 500..");
+    scan.fill((500, 0));
+
+    debug!("{}", scan);
+
+    Ok(scan)
+}
+
+/// Counts the spots that are `wet` between the topmost and bottommost clay.
+fn count_in_scan(scan: &ClayScan, wet: &[Ground]) -> usize {
+    scan.ground
+        .iter()
+        .filter(|((_, y), g)| *y >= scan.range_y.0 && wet.contains(g))
+        .count()
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let scan = parse_scan(input)?;
+        Ok(Answer::new(count_in_scan(
+            &scan,
+            &[Ground::Flowing, Ground::Settled],
+        )))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let scan = parse_scan(input)?;
+        Ok(Answer::new(count_in_scan(&scan, &[Ground::Settled])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = "x=495, y=2..7
+y=7, x=495..501
+x=501, y=3..7
+x=498, y=2..4
+x=506, y=1..2
+x=498, y=10..13
+x=504, y=10..13
+y=13, x=498..504
+";
+        assert_eq!("57", Day17.part1(input).unwrap().to_string());
+        assert_eq!("29", Day17.part2(input).unwrap().to_string());
+        assert!(parse_scan("z=495, y=2..7").is_err());
+        assert!(parse_scan("x=495, y=7..2").is_err());
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{grid::Grid, synthetic::XorShift};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day18::Forest;
+
+/// A square forest `dim` acres on a side, with acres picked at random.
+fn forest(dim: usize) -> Forest {
+    let mut rng = XorShift::new(18);
+    let mut map = Grid::new((0, 0), dim, dim, '.');
+    for y in 0..dim as i64 {
+        for x in 0..dim as i64 {
+            map[(x, y)] = rng.pick(b"..|#") as char;
+        }
+    }
+    Forest::new(map)
+}
+
+fn bench_forest_next(c: &mut Criterion) {
+    let mut group = c.benchmark_group("forest_next");
+    for &dim in &[10, 50, 100] {
+        let forest = forest(dim);
+        group.bench_with_input(BenchmarkId::from_parameter(dim), &forest, |b, forest| {
+            b.iter(|| forest.next())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_forest_next);
+criterion_main!(benches);
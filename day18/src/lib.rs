@@ -0,0 +1,153 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{
+    grid::{Grid, Point},
+    Answer, ParseError, Solution,
+};
+use log::debug;
+use std::{collections::HashMap, fmt};
+
+pub struct Forest {
+    map: Grid<char>,
+}
+
+impl Forest {
+    pub fn new(map: Grid<char>) -> Forest {
+        Forest { map }
+    }
+
+    fn count_neighbors(&self, p: Point, acre: char) -> usize {
+        self.map
+            .neighbors8(p)
+            .filter(|&n| self.map[n] == acre)
+            .count()
+    }
+
+    /// The forest after another minute.
+    pub fn next(&self) -> Forest {
+        let mut map = self.map.clone();
+        for (p, acre) in self.map.iter() {
+            map[p] = match acre {
+                '.' => {
+                    if self.count_neighbors(p, '|') >= 3 {
+                        '|'
+                    } else {
+                        '.'
+                    }
+                }
+                '|' => {
+                    if self.count_neighbors(p, '#') >= 3 {
+                        '#'
+                    } else {
+                        '|'
+                    }
+                }
+                '#' => {
+                    if self.count_neighbors(p, '#') >= 1 && self.count_neighbors(p, '|') >= 1 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                }
+                x => panic!("What is in your forest!?!? a {}!?", x),
+            };
+        }
+        Forest { map }
+    }
+
+    fn count(&self, acre: char) -> u64 {
+        self.map.iter().filter(|(_, &x)| x == acre).count() as u64
+    }
+
+    fn value(&self) -> u64 {
+        let yards = self.count('#');
+        let trees = self.count('|');
+        yards * trees
+    }
+}
+
+impl fmt::Display for Forest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.map)?;
+        write!(f, "Value: {}", self.value())?;
+        Ok(())
+    }
+}
+
+/// Runs the forest for `minutes` minutes and returns the value at the end.  The forest falls into
+/// a cycle eventually, so once a forest repeats we can skip ahead, and the answer says where the
+/// cycle started and how long it is.
+fn value_after(mut forest: Forest, minutes: usize) -> Answer {
+    let mut seen: HashMap<Grid<char>, usize> = HashMap::new();
+    let mut mins = 0;
+    while mins < minutes {
+        if let Some(prev) = seen.insert(forest.map.clone(), mins) {
+            let cycle = mins - prev;
+            let remaining = (minutes - mins) % cycle;
+            for _ in 0..remaining {
+                forest = forest.next();
+            }
+            return Answer::new(forest.value())
+                .with_stat("cycle_start", prev)
+                .with_stat("cycle_length", cycle);
+        }
+        forest = forest.next();
+        mins += 1;
+        debug!("{}. {}", mins, forest.value());
+    }
+    Answer::new(forest.value())
+}
+
+/// The forest is a square of open ground (.), trees (|) and lumberyards (#).
+fn parse_forest(input: &str) -> Result<Forest, ParseError> {
+    let map = Grid::parse(input, |c| match c {
+        '.' | '|' | '#' => Ok(c),
+        _ => Err("not an acre of forest"),
+    })?;
+    if map.width() != map.height() {
+        let line = input.lines().next().unwrap_or("");
+        return Err(ParseError::line(1, line, "the forest isn't square"));
+    }
+    let forest = Forest::new(map);
+    debug!("Initial forest: \n{}", forest);
+    Ok(forest)
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        Ok(value_after(parse_forest(input)?, 10))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        Ok(value_after(parse_forest(input)?, 1_000_000_000))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = ".#.#...|#.
+.....#|##|
+.|..|...#.
+..|#.....#
+#.#|||#|#|
+...#.||...
+.|....|...
+||...#|.#|
+|.||||..|.
+...#.|..|.
+";
+        assert_eq!(
+            "1147",
+            value_after(parse_forest(input).unwrap(), 10).to_string()
+        );
+        assert!(parse_forest(&input.replace("|.#", "|x#")).is_err());
+        assert!(parse_forest(&input[1..]).is_err());
+    }
+}
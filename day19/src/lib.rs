@@ -0,0 +1,33 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{Answer, ParseError, Solution};
+use elfcode::{Machine, Program};
+use log::info;
+
+/// The main loop adds up all the divisors of r3 into r0, very slowly.  The optimizer knows that
+/// loop, so the machine does it in one step.
+fn r0_at_halt(program: Program<u64>, r0: u64) -> Result<u64, ParseError> {
+    let program = program.optimize();
+    for found in program.macros() {
+        info!("Optimized {}", found);
+    }
+    let mut machine: Machine<u64, 6> =
+        Machine::new(program).map_err(|fault| ParseError::fault(&fault.to_string()))?;
+    machine.registers_mut()[0] = r0;
+    let steps = machine.run().map_err(|fault| ParseError::fault(&fault.to_string()))?;
+    info!("Halted after {} steps: {:?}", steps, machine.registers());
+    Ok(machine.registers()[0])
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        Ok(Answer::new(r0_at_halt(Program::parse(input, 6)?, 0)?))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        Ok(Answer::new(r0_at_halt(Program::parse(input, 6)?, 1)?))
+    }
+}
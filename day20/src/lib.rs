@@ -0,0 +1,249 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{
+    grid::{Grid, Point, NEIGHBORS_4},
+    path, Answer, ParseError, Solution,
+};
+use log::debug;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// A set of places on the map.
+type Places = HashSet<Point>;
+/// Shortest distance from the start to each reachable room.
+type DistanceMap = HashMap<Point, usize>;
+
+/// The map of the rooms, drawn like the puzzle does: rooms are `.`, doors are `|` or `-` and
+/// everything else is wall.  We start in the room at (0, 0).
+struct Map {
+    map: Grid<char>,
+}
+
+/// Build sets of the doors and rooms attached to an open space at x, y given the (partial) regex.
+fn build_doors_rooms(regex: &str, cursors: &Places) -> (Places, Places, Places) {
+    let mut chars = regex.chars();
+    let mut running_cursors = cursors.clone();
+    let mut doors_found = HashSet::new();
+    // We're at least standing in a room
+    let mut rooms_found: Places = running_cursors.iter().cloned().collect();
+    //println!("Building map: {}, {:?}", regex, running_cursors);
+    while let Some(r) = chars.next() {
+        match r {
+            'N' | 'S' | 'E' | 'W' => {
+                let d = match r {
+                    'N' => (0, -1),
+                    'S' => (0, 1),
+                    'E' => (1, 0),
+                    _ => (-1, 0),
+                };
+                for at in &running_cursors {
+                    // There's a door that way
+                    doors_found.insert((at.0 + d.0, at.1 + d.1));
+                    // and an open room beyond
+                    rooms_found.insert((at.0 + 2 * d.0, at.1 + 2 * d.1));
+                }
+                // travel to them.
+                running_cursors = running_cursors
+                    .into_iter()
+                    .map(|(x, y)| (x + 2 * d.0, y + 2 * d.1))
+                    .collect();
+            }
+            '(' => {
+                // We've started a choose your adventure. Grab the whole regex.
+                let mut cyoa = String::new();
+                let mut nested = 0;
+                let mut end_cursors = HashSet::new();
+                for r in chars.by_ref() {
+                    if nested == 0 {
+                        if r == ')' {
+                            // we've found our matching close bracket
+                            let (new_doors, new_rooms, new_cursors) =
+                                build_doors_rooms(&cyoa, &running_cursors);
+                            // Add where we ended up (this is the first half)
+                            for cursor in new_cursors {
+                                end_cursors.insert(cursor);
+                            }
+                            for door in new_doors {
+                                doors_found.insert(door);
+                            }
+                            for room in new_rooms {
+                                rooms_found.insert(room);
+                            }
+                            break;
+                        } else if r == '|' {
+                            // we've found our branch, explore the first then start a new branch
+                            let (new_doors, new_rooms, new_cursors) =
+                                build_doors_rooms(&cyoa, &running_cursors);
+                            // Add where we ended up (this is the first half)
+                            for door in new_doors {
+                                doors_found.insert(door);
+                            }
+                            for room in new_rooms {
+                                rooms_found.insert(room);
+                            }
+                            end_cursors = new_cursors;
+                            // Continue on to build the other half of the string
+                            cyoa = String::new();
+                            continue;
+                        }
+                    }
+                    cyoa.push(r);
+                    if r == '(' {
+                        nested += 1;
+                    } else if r == ')' {
+                        nested -= 1;
+                    }
+                }
+                running_cursors = end_cursors;
+            }
+            // We shouldn't be able to encounter anything else.
+            what => panic!("Unexpected character while dungeoncrawling: {}", what),
+        }
+    }
+    (doors_found, rooms_found, running_cursors)
+}
+
+impl Map {
+    fn from_regex(regex: &str) -> Map {
+        let mut start = HashSet::new();
+        start.insert((0, 0));
+        let (doors, rooms, _) = build_doors_rooms(&regex[1..regex.len() - 1], &start);
+        let min_x = rooms.iter().map(|r| r.0).min().unwrap();
+        let max_x = rooms.iter().map(|r| r.0).max().unwrap();
+        let min_y = rooms.iter().map(|r| r.1).min().unwrap();
+        let max_y = rooms.iter().map(|r| r.1).max().unwrap();
+        let width = (max_x - min_x + 3) as usize;
+        let height = (max_y - min_y + 3) as usize;
+        let mut map = Grid::new((min_x - 1, min_y - 1), width, height, '#');
+        for &room in &rooms {
+            map[room] = '.';
+        }
+        for &(x, y) in &doors {
+            map[(x, y)] = if rooms.contains(&(x - 1, y)) {
+                '|'
+            } else {
+                '-'
+            };
+        }
+        Map { map }
+    }
+
+    fn neighbors(&self, at: &Point) -> Vec<Point> {
+        NEIGHBORS_4
+            .iter()
+            .filter(|d| {
+                matches!(
+                    self.map.get((at.0 + d.0, at.1 + d.1)),
+                    Some('|') | Some('-')
+                )
+            })
+            .map(|d| (at.0 + 2 * d.0, at.1 + 2 * d.1))
+            .collect()
+    }
+
+    /// How many doors it takes to get to each room from `at`.
+    fn distances_from(&self, at: &Point) -> DistanceMap {
+        path::bfs(*at, |u| self.neighbors(u)).into_distances()
+    }
+
+    fn doors_to_furthest_room(&self) -> usize {
+        let dist = self.distances_from(&(0, 0));
+        dist.iter().map(|(_, &dist)| dist).max().unwrap()
+    }
+
+    fn gte_n_doors_away(&self, n: &usize) -> usize {
+        let dist = self.distances_from(&(0, 0));
+        dist.iter()
+            .map(|(_, &dist)| dist)
+            .filter(|dist| dist >= n)
+            .count()
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.map)
+    }
+}
+
+/// Checks that the regex is only directions, with balanced brackets, between ^ and $.
+fn check_regex(regex: &str) -> Result<(), ParseError> {
+    if !regex.starts_with('^') || !regex.ends_with('$') || regex.len() < 2 {
+        return Err(ParseError::line(
+            1,
+            regex,
+            "the regex should be between ^ and $",
+        ));
+    }
+    let mut open_brackets = Vec::new();
+    for (idx, c) in regex.char_indices().skip(1).take(regex.len() - 2) {
+        let here = &regex[idx..idx + c.len_utf8()];
+        match c {
+            'N' | 'S' | 'E' | 'W' | '|' => (),
+            '(' => open_brackets.push(here),
+            ')' => {
+                if open_brackets.pop().is_none() {
+                    return Err(ParseError::at(1, regex, here, "nothing to close"));
+                }
+            }
+            _ => return Err(ParseError::at(1, regex, here, "not a direction")),
+        }
+    }
+    match open_brackets.pop() {
+        Some(bracket) => Err(ParseError::at(1, regex, bracket, "never closed")),
+        None => Ok(()),
+    }
+}
+
+fn parse_map(input: &str) -> Result<Map, ParseError> {
+    let regex = input.lines().next().unwrap_or("").trim_end();
+    check_regex(regex)?;
+    let room_map = Map::from_regex(regex);
+    debug!("{}\n\n{}", regex, room_map);
+    Ok(room_map)
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let room_map = parse_map(input)?;
+        Ok(Answer::new(room_map.doors_to_furthest_room()))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let room_map = parse_map(input)?;
+        Ok(Answer::new(room_map.gte_n_doors_away(&1000)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_furthest_room() {
+        let expected = [3, 10, 18, 23, 31];
+        for (regex, furthest) in include_str!("../input.xmpl").lines().zip(expected.iter()) {
+            assert_eq!(*furthest, Map::from_regex(regex).doors_to_furthest_room());
+        }
+        assert_eq!(
+            "#####\n#.|.#\n#-###\n#.|.#\n#####\n",
+            Map::from_regex("^WNE$").to_string()
+        );
+    }
+
+    #[test]
+    fn test_check_regex() {
+        for regex in include_str!("../input.xmpl").lines() {
+            assert_eq!(Ok(()), check_regex(regex));
+        }
+        assert_eq!(1, check_regex("WNE$").err().unwrap().column);
+        assert_eq!(4, check_regex("^WNX$").err().unwrap().column);
+        assert_eq!(4, check_regex("^EN)$").err().unwrap().column);
+        assert_eq!(3, check_regex("^E(N|S$").err().unwrap().column);
+    }
+}
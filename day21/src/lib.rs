@@ -0,0 +1,59 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{Answer, ParseError, Solution};
+use elfcode::{halting_inputs, Halting, Machine, Program};
+use log::{debug, info};
+
+const NO_HALT: &str = "no value of r0 halts the program";
+
+/// Searches the program for the values of r0 that halt it.
+fn halting(input: &str) -> Result<Halting<u64>, ParseError> {
+    // First star:
+    // Decompiled (`cargo run -p elfcode -- decompile day21/input.txt`), the only instruction
+    // that reads r0 is instruction 28, which checks if r0 is equal to r4 then halts.
+    // The first r4 it's compared with halts it soonest.
+    //
+    // Second star:
+    // There must be a loop in the values that r4 takes in instruction 28.  The search runs until
+    // it does, and the value it compared with for the first time last makes the longest run.
+    //
+    // The subroutine at 18 divides r2 by 256 a step at a time, which the optimizer does at once.
+    // The steps the search counts are still every instruction the loop would have run.
+
+    let program = Program::parse(input, 6)?.optimize();
+    for found in program.macros() {
+        info!("Optimized {}", found);
+    }
+    let machine: Machine<u64, 6> =
+        Machine::new(program).map_err(|fault| ParseError::fault(&fault.to_string()))?;
+    let halting = halting_inputs(machine, 0, 100_000_000)
+        .map_err(|e| ParseError::fault(&e.to_string()))?;
+    info!(
+        "Compared r0 at {:?}, {} times before repeating",
+        halting.comparisons, halting.visits
+    );
+    debug!("Halting inputs: {:?}", halting.inputs);
+    Ok(halting)
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let fewest = halting(input)?
+            .fewest_steps()
+            .ok_or_else(|| ParseError::fault(NO_HALT))?;
+        Ok(Answer::new(fewest.value).with_stat("steps", fewest.steps))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let halting = halting(input)?;
+        let most = halting
+            .most_steps()
+            .ok_or_else(|| ParseError::fault(NO_HALT))?;
+        Ok(Answer::new(most.value)
+            .with_stat("steps", most.steps)
+            .with_stat("distinct_values", halting.inputs.len()))
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use day22::{Cave, Tool};
+
+fn bench_time_to_target(c: &mut Criterion) {
+    let mut group = c.benchmark_group("time_to_target");
+    group.sample_size(10);
+    for &target in &[(10, 10), (10, 50), (10, 100)] {
+        let id = format!("{}x{}", target.0, target.1);
+        group.bench_with_input(BenchmarkId::from_parameter(id), &target, |b, target| {
+            b.iter_batched(
+                || Cave::new(&510, target),
+                |mut cave| {
+                    cave.time_to_target(&(0, 0, Tool::Torch), &(target.0, target.1, Tool::Torch))
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_time_to_target);
+criterion_main!(benches);
@@ -0,0 +1,258 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, path, Answer, ParseError, Solution};
+use log::{debug, trace};
+use std::{collections::HashMap, fmt};
+
+enum Terrain {
+    Rocky,
+    Wet,
+    Narrow,
+}
+
+impl Terrain {
+    fn from_erosion(level: &usize) -> Terrain {
+        match level % 3 {
+            0 => Terrain::Rocky,
+            1 => Terrain::Wet,
+            2 => Terrain::Narrow,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl fmt::Display for Terrain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Terrain::Rocky => '.',
+                Terrain::Wet => '=',
+                Terrain::Narrow => '|',
+            }
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum Tool {
+    Torch,
+    Climbing,
+    Neither,
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Tool::Torch => "Torch",
+                Tool::Climbing => "Climbing",
+                Tool::Neither => "Neither",
+            }
+        )
+    }
+}
+
+pub struct Cave {
+    /// Map from x, y to erosion level
+    map: HashMap<(usize, usize), usize>,
+    target: (usize, usize),
+    depth: usize,
+}
+
+impl Cave {
+    pub fn new(depth: &usize, target: &(usize, usize)) -> Cave {
+        let mut map = HashMap::new();
+        for x in 0..target.0 + 300 {
+            for y in 0..target.1 + 300 {
+                let geo_lvl = if x == target.0 && y == target.1 {
+                    0
+                } else if x == 0 {
+                    y * 48271
+                } else if y == 0 {
+                    x * 16807
+                } else {
+                    map.get(&(x - 1, y)).unwrap() * map.get(&(x, y - 1)).unwrap()
+                };
+
+                let ero_level = (geo_lvl + depth) % 20183;
+                map.insert((x, y), ero_level);
+            }
+        }
+        Cave {
+            map,
+            depth: *depth,
+            target: *target,
+        }
+    }
+
+    fn erosion(&mut self, at: &(usize, usize)) -> usize {
+        match self.map.get(at) {
+            Some(lvl) => *lvl,
+            None => {
+                let new_geo_lvl = if at.0 == 0 {
+                    at.1 * 48271
+                } else if at.1 == 0 {
+                    at.0 * 16807
+                } else {
+                    self.erosion(&(at.0 - 1, at.1)) * self.erosion(&(at.0, at.1 - 1))
+                };
+                let ero_lvl = (new_geo_lvl + self.depth) % 20183;
+                self.map.insert(*at, ero_lvl);
+                ero_lvl
+            }
+        }
+    }
+
+    fn terrain(&mut self, at: &(usize, usize)) -> Terrain {
+        Terrain::from_erosion(&self.erosion(at))
+    }
+
+    /// Returns the neighbors that are valid, with their time.
+    fn neighbors_time(
+        &mut self,
+        at: &(usize, usize, Tool),
+    ) -> HashMap<(usize, usize, Tool), usize> {
+        let mut n = HashMap::new();
+        // Could switch tools
+        match (self.terrain(&(at.0, at.1)), &at.2) {
+            (Terrain::Rocky, Tool::Climbing) => n.insert((at.0, at.1, Tool::Torch), 7),
+            (Terrain::Rocky, Tool::Torch) => n.insert((at.0, at.1, Tool::Climbing), 7),
+            (Terrain::Wet, Tool::Climbing) => n.insert((at.0, at.1, Tool::Neither), 7),
+            (Terrain::Wet, Tool::Neither) => n.insert((at.0, at.1, Tool::Climbing), 7),
+            (Terrain::Narrow, Tool::Neither) => n.insert((at.0, at.1, Tool::Torch), 7),
+            (Terrain::Narrow, Tool::Torch) => n.insert((at.0, at.1, Tool::Neither), 7),
+            (t, o) => panic!("Holding an invalid tool: {} at {}", t, o),
+        };
+        // Could move around instead.
+        let mut shifts = Vec::new();
+        if at.0 > 0 {
+            shifts.push((at.0 - 1, at.1));
+        }
+        if at.1 > 0 {
+            shifts.push((at.0, at.1 - 1));
+        }
+        shifts.push((at.0 + 1, at.1));
+        shifts.push((at.0, at.1 + 1));
+        for shift in shifts {
+            match (self.terrain(&shift), &at.2) {
+                (Terrain::Rocky, Tool::Climbing) => n.insert((shift.0, shift.1, Tool::Climbing), 1),
+                (Terrain::Rocky, Tool::Torch) => n.insert((shift.0, shift.1, Tool::Torch), 1),
+                (Terrain::Wet, Tool::Climbing) => n.insert((shift.0, shift.1, Tool::Climbing), 1),
+                (Terrain::Wet, Tool::Neither) => n.insert((shift.0, shift.1, Tool::Neither), 1),
+                (Terrain::Narrow, Tool::Neither) => n.insert((shift.0, shift.1, Tool::Neither), 1),
+                (Terrain::Narrow, Tool::Torch) => n.insert((shift.0, shift.1, Tool::Torch), 1),
+                _ => None,
+            };
+        }
+        n
+    }
+
+    /// The shortest time to get from `at` to `target`, holding the right tool at each.  It takes
+    /// at least a minute per region to get there, and seven more to switch tools if we have the
+    /// wrong one, so that's the A* guess.
+    pub fn time_to_target(
+        &mut self,
+        at: &(usize, usize, Tool),
+        target: &(usize, usize, Tool),
+    ) -> usize {
+        let guess = |u: &(usize, usize, Tool)| {
+            let switch = if u.2 == target.2 { 0 } else { 7 };
+            u.0.abs_diff(target.0) + u.1.abs_diff(target.1) + switch
+        };
+        let neighbors = |u: &(usize, usize, Tool)| {
+            trace!("Exploring options from {:?}", u);
+            self.neighbors_time(u)
+        };
+        let (time, route) =
+            path::astar(*at, target, neighbors, guess).expect("The target can always be reached");
+        debug!("Took {} minutes over {} steps", time, route.len() - 1);
+        time
+    }
+
+    /// The sum of the risk of the regions between the mouth of the cave and the target.
+    fn risk_level(&self) -> usize {
+        self.map
+            .iter()
+            .filter(|((x, y), _)| x <= &self.target.0 && y <= &self.target.1)
+            .map(|(_, v)| v % 3)
+            .sum()
+    }
+}
+
+impl fmt::Display for Cave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.target.1 + 1 {
+            for x in 0..self.target.0 + 1 {
+                if x == 0 && y == 0 {
+                    write!(f, "M")?;
+                } else if x == self.target.0 && y == self.target.1 {
+                    write!(f, "T")?;
+                } else {
+                    write!(
+                        f,
+                        "{}",
+                        Terrain::from_erosion(self.map.get(&(x, y)).unwrap())
+                    )?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the scan of the cave, which is "depth: N" then "target: X,Y".
+fn parse_cave(input: &str) -> Result<Cave, ParseError> {
+    let mut lines = numbered_lines(input);
+    let mut line_with = |line_no: usize, label: &str, count: usize| {
+        let (line_no, line) = lines.next().unwrap_or((line_no, ""));
+        if !line.starts_with(label) {
+            let message = format!("expected \"{}\"", label);
+            return Err(ParseError::line(line_no, line, &message));
+        }
+        parse_numbers(line_no, line, count)
+    };
+    let depth = line_with(1, "depth: ", 1)?;
+    let target = line_with(2, "target: ", 2)?;
+    let cave = Cave::new(&depth[0], &(target[0], target[1]));
+    debug!("{}", cave);
+    Ok(cave)
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let cave = parse_cave(input)?;
+        Ok(Answer::new(cave.risk_level()))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let mut cave = parse_cave(input)?;
+        let target = (cave.target.0, cave.target.1, Tool::Torch);
+        let time = cave.time_to_target(&(0, 0, Tool::Torch), &target);
+        Ok(Answer::new(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = "depth: 510\ntarget: 10,10\n";
+        assert_eq!("114", Day22.part1(input).unwrap().to_string());
+        assert_eq!("45", Day22.part2(input).unwrap().to_string());
+        assert_eq!(2, parse_cave("depth: 510\n").err().unwrap().line);
+        assert_eq!(
+            1,
+            parse_cave("depth 510\ntarget: 10,10").err().unwrap().line
+        );
+    }
+}
@@ -0,0 +1,397 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, Answer, ParseError, Solution};
+use log::{debug, info, trace};
+use std::{
+    cmp::{min, Ordering, Reverse},
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+#[derive(Clone, PartialEq)]
+enum Side {
+    Immune,
+    Infection,
+    Stalemate,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self == &Side::Immune {
+            write!(f, "Immune")?;
+        } else {
+            write!(f, "Infection")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct Army {
+    side: Side,
+    units: u64,
+    hitpoints: u64,
+    weak_to: HashSet<String>,
+    immune_to: HashSet<String>,
+    damage: u64,
+    damage_type: String,
+    initiative: u64,
+}
+
+impl Army {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        side: Side,
+        units: u64,
+        hitpoints: u64,
+        weak_to: &[String],
+        immune_to: &[String],
+        damage: u64,
+        damage_type: String,
+        initiative: u64,
+    ) -> Army {
+        let mut a = Army {
+            side,
+            units,
+            hitpoints,
+            damage,
+            initiative,
+            damage_type,
+            weak_to: HashSet::new(),
+            immune_to: HashSet::new(),
+        };
+        for weak in weak_to {
+            a.weak_to.insert(weak.to_string());
+        }
+        for immune in immune_to {
+            a.immune_to.insert(immune.to_string());
+        }
+        a
+    }
+
+    fn is_side(&self, side: &Side) -> bool {
+        &self.side == side
+    }
+
+    fn effective_power(&self) -> u64 {
+        self.units * self.damage
+    }
+
+    fn target_selection_order(&self) -> (u64, u64) {
+        (self.effective_power(), self.initiative)
+    }
+
+    fn would_damage(&self, other: &Army) -> u64 {
+        if other.immune_to.contains(&self.damage_type) {
+            return 0;
+        }
+        if other.weak_to.contains(&self.damage_type) {
+            return 2 * self.damage * self.units;
+        }
+        self.damage * self.units
+    }
+
+    fn targeting_cmp(&self, a: &Army, b: &Army) -> Ordering {
+        if self.would_damage(a) > self.would_damage(b) {
+            return Ordering::Less;
+        }
+        if self.would_damage(b) > self.would_damage(a) {
+            return Ordering::Greater;
+        }
+        if a.effective_power() > b.effective_power() {
+            return Ordering::Less;
+        }
+        if a.effective_power() < b.effective_power() {
+            return Ordering::Greater;
+        }
+        if a.initiative > b.initiative {
+            return Ordering::Less;
+        }
+        if a.initiative < b.initiative {
+            return Ordering::Greater;
+        }
+        Ordering::Equal
+    }
+
+    fn take_damage(&mut self, amt: u64) -> u64 {
+        let units_lost = min(amt / self.hitpoints, self.units);
+        self.units -= units_lost;
+        units_lost
+    }
+}
+
+impl fmt::Display for Army {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} group has {} units each with {} hit points",
+            self.side, self.units, self.hitpoints
+        )?;
+        if !self.weak_to.is_empty() {
+            write!(
+                f,
+                " weak to {}",
+                self.weak_to
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )?;
+        }
+        if !self.immune_to.is_empty() {
+            write!(
+                f,
+                " immune to {}",
+                self.immune_to
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )?;
+        }
+        write!(
+            f,
+            " with an attack that does {} {} damage at initiative {}",
+            self.damage, self.damage_type, self.initiative
+        )?;
+        Ok(())
+    }
+}
+
+/// Parses one group line, like "17 units each with 5390 hit points (weak to radiation,
+/// bludgeoning) with an attack that does 4507 fire damage at initiative 2".
+fn parse_army(line_no: usize, side: Side, line: &str) -> Result<Army, ParseError> {
+    let nums: Vec<u64> = parse_numbers(line_no, line, 4)?;
+    let mut weak_to = Vec::new();
+    let mut immune_to = Vec::new();
+    if let (Some(start), Some(end)) = (line.find('('), line.find(')')) {
+        for part in line[start + 1..end].split("; ") {
+            if let Some(kinds) = part.strip_prefix("weak to ") {
+                weak_to.extend(kinds.split(", ").map(String::from));
+            } else if let Some(kinds) = part.strip_prefix("immune to ") {
+                immune_to.extend(kinds.split(", ").map(String::from));
+            } else {
+                return Err(ParseError::at(line_no, line, part, "expected weak to or immune to"));
+            }
+        }
+    }
+    let damage_type = match line.find(" damage") {
+        Some(end) => line[..end].split_whitespace().last().unwrap_or(""),
+        None => return Err(ParseError::line(line_no, line, "this group doesn't do any damage")),
+    };
+    if !damage_type.chars().all(|c| c.is_ascii_lowercase()) {
+        return Err(ParseError::at(line_no, line, damage_type, "not a kind of damage"));
+    }
+    let damage_type = damage_type.to_string();
+    Ok(Army::new(side, nums[0], nums[1], &weak_to, &immune_to, nums[2], damage_type, nums[3]))
+}
+
+fn parse_armies(input: &str) -> Result<Vec<Army>, ParseError> {
+    let mut armies = Vec::new();
+    let mut side = None;
+    for (line_no, line) in numbered_lines(input).filter(|(_, l)| !l.trim().is_empty()) {
+        match line.trim() {
+            "Immune System:" => side = Some(Side::Immune),
+            "Infection:" => side = Some(Side::Infection),
+            _ => match &side {
+                Some(side) => armies.push(parse_army(line_no, side.clone(), line)?),
+                None => return Err(ParseError::line(line_no, line, "this group isn't on a side")),
+            },
+        }
+    }
+    for (side, name) in &[(Side::Immune, "immune system"), (Side::Infection, "infection")] {
+        if !armies.iter().any(|army| army.is_side(side)) {
+            return Err(ParseError::line(1, "", &format!("there's no {}", name)));
+        }
+    }
+    Ok(armies)
+}
+
+/// Fights the battle with the immune system boosted by `boost_amount`.  Returns the winning side
+/// and how many units it has left.
+fn battle_with_boost(initial: &[Army], boost_amount: u64) -> (Side, u64) {
+    let mut armies = initial.to_vec();
+    for army in armies.iter_mut().filter(|x| x.is_side(&Side::Immune)) {
+        army.damage += boost_amount;
+    }
+
+    loop {
+        // Target selection
+        armies.sort_unstable_by_key(|x| Reverse(x.target_selection_order()));
+        let units_before = armies.iter().fold(0, |a, x| a + x.units);
+
+        // A map from attackers to attackees, now that we won't reorder them
+        let mut attacks = HashMap::new();
+        let mut attacked_by = HashMap::new();
+
+        for (idx, army) in armies.iter().enumerate() {
+            let mut candidate_target_idx: Vec<usize> = (0..armies.len())
+                .filter(|&x| !armies[x].is_side(&army.side))
+                .filter(|x| !attacked_by.contains_key(x))
+                .collect();
+            candidate_target_idx
+                .sort_unstable_by(|&a, &b| army.targeting_cmp(&armies[a], &armies[b]));
+            if candidate_target_idx.is_empty()
+                || army.would_damage(&armies[candidate_target_idx[0]]) == 0
+            {
+                // No target.
+                trace!("Army {} has no valid targets, not attacking.", idx);
+                continue;
+            }
+            attacks.insert(idx, candidate_target_idx[0]);
+            attacked_by.insert(candidate_target_idx[0], idx);
+        }
+
+        trace!("### Attacking phase ###");
+        // Attacking phase.
+        let mut attack_order: Vec<usize> = (0..armies.len()).collect();
+        attack_order.sort_unstable_by(|&a, &b| armies[b].initiative.cmp(&armies[a].initiative));
+        for idx in attack_order {
+            if attacks.contains_key(&idx) {
+                let damage = armies[idx].would_damage(&armies[attacks[&idx]]);
+                let units_lost = armies[attacks[&idx]].take_damage(damage);
+                trace!(
+                    "Army {} attacks army {}, doing {} damage and destroying {} units",
+                    idx,
+                    attacks[&idx],
+                    damage,
+                    units_lost
+                );
+            }
+        }
+
+        // Remove armies with no units left
+        armies.retain(|x| x.units > 0);
+
+        // If one side has no armies, we are done.
+        let (immune, infect): (Vec<&Army>, Vec<&Army>) =
+            armies.iter().partition(|x| x.is_side(&Side::Immune));
+        if immune.is_empty() || infect.is_empty() {
+            break;
+        }
+        let units_after = armies.iter().fold(0, |a, x| a + x.units);
+        if units_before == units_after {
+            // No units have died, and we are in a stalemate.
+            return (Side::Stalemate, units_after);
+        }
+    }
+
+    info!("These armies are left after the glorious battle:");
+    let mut total_units = 0;
+    for army in &armies {
+        info!("{}", army);
+        total_units += army.units;
+    }
+    info!("There are {} units in the winning army.", total_units);
+    let winner = armies.first().map_or(Side::Stalemate, |army| army.side.clone());
+    (winner, total_units)
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        let armies = parse_armies(input)?;
+        let (winner, units) = battle_with_boost(&armies, 0);
+        Ok(Answer::new(units).with_stat("winner", winner.to_string()))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, ParseError> {
+        let armies = parse_armies(input)?;
+        // No boost wins if there's an infection group nothing in the immune system can hurt.
+        let (immune, infection): (Vec<&Army>, Vec<&Army>) =
+            armies.iter().partition(|army| army.is_side(&Side::Immune));
+        if let Some(unhurt) = infection.iter().find(|infected| {
+            immune.iter().all(|army| infected.immune_to.contains(&army.damage_type))
+        }) {
+            let message = format!("the immune system can't hurt the {}", unhurt);
+            return Err(ParseError::fault(&message));
+        }
+        // Past this, one unit wipes out any group it can hurt in one go, so boosting it more
+        // doesn't change anything.
+        let most = infection
+            .iter()
+            .map(|army| army.units.saturating_mul(army.hitpoints))
+            .max()
+            .unwrap_or(0);
+        let wins = |boost| {
+            debug!("Boosting immune system by {}", boost);
+            match battle_with_boost(&armies, boost) {
+                (Side::Immune, units) => Some(units),
+                _ => None,
+            }
+        };
+        // Doubles the boost until the immune system wins, then halves the gap to the last boost
+        // that didn't, taking it that more boost never loses.
+        let (mut lost, mut won) = (0, 1);
+        let mut units = loop {
+            if let Some(units) = wins(won) {
+                break units;
+            }
+            if won >= most {
+                return Err(ParseError::fault("no boost lets the immune system win"));
+            }
+            lost = won;
+            won = min(won.saturating_mul(2), most);
+        };
+        while won - lost > 1 {
+            let boost = lost + (won - lost) / 2;
+            match wins(boost) {
+                Some(left) => {
+                    won = boost;
+                    units = left;
+                }
+                None => lost = boost,
+            }
+        }
+        Ok(Answer::new(units).with_stat("boost", won))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_common::{ErrorKind, Stat};
+
+    const EXAMPLE: &str = "Immune System:
+17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+989 units each with 1274 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 25 slashing damage at initiative 3
+
+Infection:
+801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4
+";
+
+    #[test]
+    fn test_example() {
+        let armies = parse_armies(EXAMPLE).unwrap();
+        assert_eq!(4, armies.len());
+        assert!(battle_with_boost(&armies, 0) == (Side::Infection, 5216));
+        assert!(battle_with_boost(&armies, 1570) == (Side::Immune, 51));
+        let answer = Day24.part2(EXAMPLE).unwrap();
+        assert_eq!("51", answer.to_string());
+        assert_eq!(&[("boost", Stat::Number(1570))], answer.stats());
+    }
+
+    #[test]
+    fn test_unbeatable() {
+        // Nothing in the immune system does anything but fire and slashing damage.
+        let input = EXAMPLE.replace("immune to radiation;", "immune to slashing, fire;");
+        let err = Day24.part2(&input).err().unwrap();
+        assert_eq!(ErrorKind::Fault, err.kind);
+        assert!(err.message.starts_with("the immune system can't hurt"), "{}", err.message);
+    }
+
+    #[test]
+    fn test_bad_armies() {
+        let err = parse_armies(&EXAMPLE.replace("weak to radiation)", "weak against radiation)"))
+            .err()
+            .unwrap();
+        assert_eq!((6, 38), (err.line, err.column));
+        let err = parse_armies(&EXAMPLE.replace("Immune System:\n", "")).err().unwrap();
+        assert_eq!(1, err.line);
+        let err = parse_armies("").err().unwrap();
+        assert_eq!("there's no immune system", err.message);
+    }
+}
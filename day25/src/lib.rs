@@ -0,0 +1,121 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use aoc_common::{numbered_lines, parse_numbers, Answer, ParseError, Solution};
+use log::{debug, info};
+
+#[derive(Debug)]
+struct Star(i64, i64, i64, i64);
+
+impl Star {
+    fn new(x: i64, y: i64, z: i64, t: i64) -> Star {
+        Star(x, y, z, t)
+    }
+
+    fn dist_from(&self, other: &Star) -> i64 {
+        (self.0 - other.0).abs()
+            + (self.1 - other.1).abs()
+            + (self.2 - other.2).abs()
+            + (self.3 - other.3).abs()
+    }
+}
+
+struct Constellation {
+    stars: Vec<Star>,
+}
+
+impl Constellation {
+    fn new() -> Constellation {
+        Constellation { stars: Vec::new() }
+    }
+
+    fn attached(&self, star: &Star) -> bool {
+        self.stars.iter().find(|x| x.dist_from(star) <= 3).is_some()
+    }
+
+    fn insert(&mut self, star: Star) {
+        self.stars.push(star)
+    }
+}
+
+fn parse_stars(input: &str) -> Result<Vec<Star>, ParseError> {
+    let stars: Vec<Star> = numbered_lines(input)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_no, line)| {
+            let coords = parse_numbers(line_no, line, 4)?;
+            Ok(Star::new(coords[0], coords[1], coords[2], coords[3]))
+        })
+        .collect::<Result<_, ParseError>>()?;
+    info!("Added {} stars to the sky", stars.len());
+    Ok(stars)
+}
+
+fn constellations(mut unowned_stars: Vec<Star>) -> Vec<Constellation> {
+    let mut constellations: Vec<Constellation> = Vec::new();
+
+    while !unowned_stars.is_empty() {
+        // Start a constellation with the first star
+        let mut new_constellation = Constellation::new();
+        new_constellation.insert(unowned_stars.pop().unwrap());
+        // Keep trying to add stars to this constellation until we can't anymore
+        loop {
+            let (attached, unowned): (Vec<Star>, Vec<Star>) = unowned_stars
+                .into_iter()
+                .partition(|s| new_constellation.attached(s));
+            unowned_stars = unowned;
+            let mut count = 0;
+            for s in attached {
+                new_constellation.insert(s);
+                count += 1;
+            }
+            if count == 0 {
+                // We didn't add any stars, move on to the next constellation.
+                break;
+            }
+        }
+        constellations.push(new_constellation);
+    }
+
+    info!("There are {} constellations: ", constellations.len());
+
+    for c in &constellations {
+        debug!("{} stars: {:?}", c.stars.len(), c.stars);
+    }
+
+    constellations
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    fn part1(&self, input: &str) -> Result<Answer, ParseError> {
+        Ok(Answer::new(constellations(parse_stars(input)?).len()))
+    }
+
+    // There's no puzzle for the second part on the last day.
+    fn part2(&self, _input: &str) -> Result<Answer, ParseError> {
+        Ok(Answer::new("Merry Christmas!"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = "-1,2,2,0
+0,0,2,-2
+0,0,0,-2
+-1,2,0,0
+-2,-2,-2,2
+3,0,2,-1
+-1,3,2,2
+-1,0,-1,0
+0,2,1,-2
+3,0,0,0
+";
+        assert_eq!(4, constellations(parse_stars(input).unwrap()).len());
+        assert_eq!(2, parse_stars("0,0,0,0\n1,2,3\n").err().unwrap().line);
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use elfcode::{Compiled, Machine, Program};
+
+/// Day 21 halts the first time it checks r0 if r0 is the first answer.
+const DAY21: &str = include_str!("../../day21/input.txt");
+const R0: u64 = 11840402;
+
+fn bench_day21(c: &mut Criterion) {
+    let program: Program<u64> = Program::parse(DAY21, 6).unwrap();
+    let mut group = c.benchmark_group("day21");
+    group.bench_function("machine", |b| {
+        b.iter(|| {
+            let mut machine: Machine<u64, 6> = Machine::new(program.clone()).unwrap();
+            machine.registers_mut()[0] = R0;
+            machine.run().unwrap()
+        })
+    });
+    let compiled: Compiled<u64, 6> = Compiled::new(&program).unwrap();
+    group.bench_function("compiled", |b| {
+        b.iter(|| compiled.run(&mut [R0, 0, 0, 0, 0, 0]).unwrap())
+    });
+    let optimized: Compiled<u64, 6> = Compiled::new(&program.clone().optimize()).unwrap();
+    group.bench_function("compiled_optimized", |b| {
+        b.iter(|| optimized.run(&mut [R0, 0, 0, 0, 0, 0]).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_day21);
+criterion_main!(benches);
@@ -0,0 +1,362 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! An assembler for writing programs by hand, without working out where the jumps go:
+//!
+//! ```text
+//! #reg n 1            ; r1 is called n
+//! #ip 4
+//! start:  seti 3 _ n  ; _ is an operand that isn't used
+//! again:  muli n 2 n
+//!         gtri n 100 r2
+//!         addr r2 ip ip
+//!         jmp again
+//!         seti done _ r3
+//! done:
+//! ```
+//!
+//! Registers can be numbers, `r` and a number, a name from a `#reg` line, or `ip` once it's
+//! bound.  Values can be a label, with `+` or `-` a number after it, which is where the label is.
+//! `jmp label` goes to the label, by setting the instruction pointer to just before it.  Labels
+//! and names can be used before the line that makes them.  It comes out as the same
+//! instructions `Program::parse` reads, and a program's `Display` writes it back out that way.
+
+use crate::{Instruction, Opcode, Operand, Program, Word};
+use aoc_common::{numbered_lines, parse_number, ParseError};
+use std::collections::HashMap;
+
+/// The labels and register names in a program.
+struct Symbols<'a> {
+    registers: usize,
+    names: HashMap<&'a str, usize>,
+    labels: HashMap<&'a str, usize>,
+    ip_reg: Option<usize>,
+}
+
+/// The line without its comment, and the labels at the start of it split from the words after.
+fn split_line(line: &str) -> (Vec<&str>, Vec<&str>) {
+    let code = line.split(';').next().unwrap();
+    let mut words: Vec<&str> = code.split_whitespace().collect();
+    let count = words.iter().take_while(|word| word.ends_with(':')).count();
+    let rest = words.split_off(count);
+    (words, rest)
+}
+
+/// Checks that `text` can name a label or a register.
+fn check_name(line_no: usize, line: &str, text: &str) -> Result<(), ParseError> {
+    let mut chars = text.chars();
+    let starts = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    let looks_like_register = text.starts_with('r') && text[1..].parse::<usize>().is_ok();
+    if !starts
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || looks_like_register
+        || text == "ip"
+    {
+        return Err(ParseError::at(line_no, line, text, "not a name"));
+    }
+    Ok(())
+}
+
+impl<'a> Symbols<'a> {
+    /// Finds the labels and register names, which can be used anywhere in the program.
+    fn collect(input: &'a str, registers: usize) -> Result<Symbols<'a>, ParseError> {
+        let mut symbols = Symbols {
+            registers,
+            names: HashMap::new(),
+            labels: HashMap::new(),
+            ip_reg: None,
+        };
+        let mut at = 0;
+        for (line_no, line) in numbered_lines(input) {
+            let (labels, words) = split_line(line);
+            for label in labels {
+                let name = &label[..label.len() - 1];
+                check_name(line_no, line, name)?;
+                if symbols.labels.insert(name, at).is_some() {
+                    return Err(ParseError::at(
+                        line_no,
+                        line,
+                        name,
+                        "the label is already used",
+                    ));
+                }
+            }
+            match words.as_slice() {
+                [] | ["#ip", ..] => {}
+                ["#reg", name, reg] => {
+                    check_name(line_no, line, name)?;
+                    let reg = parse_number(line_no, line, reg.trim_start_matches('r'))?;
+                    if reg >= registers {
+                        let message = format!("there are only {} registers", registers);
+                        return Err(ParseError::at(line_no, line, words[2], &message));
+                    }
+                    if symbols.names.insert(name, reg).is_some() {
+                        return Err(ParseError::at(
+                            line_no,
+                            line,
+                            name,
+                            "the name is already used",
+                        ));
+                    }
+                }
+                [directive, ..] if directive.starts_with('#') => {
+                    return Err(ParseError::at(
+                        line_no,
+                        line,
+                        directive,
+                        "unknown directive",
+                    ));
+                }
+                _ => at += 1,
+            }
+        }
+        Ok(symbols)
+    }
+
+    /// The register `text` names.
+    fn register(&self, line_no: usize, line: &str, text: &str) -> Result<usize, ParseError> {
+        let register = match (text, self.names.get(text)) {
+            (_, Some(&register)) => register,
+            ("ip", None) => self
+                .ip_reg
+                .ok_or_else(|| ParseError::at(line_no, line, text, "the ip isn't bound"))?,
+            _ => {
+                let number = text.strip_prefix('r').unwrap_or(text);
+                number
+                    .parse()
+                    .map_err(|_| ParseError::at(line_no, line, text, "not a register"))?
+            }
+        };
+        if register >= self.registers {
+            let message = format!("there are only {} registers", self.registers);
+            return Err(ParseError::at(line_no, line, text, &message));
+        }
+        Ok(register)
+    }
+
+    /// Where the label in `text` is, with the number after it added or taken away.  It has to
+    /// fit in a word.
+    fn address<W: Word>(
+        &self,
+        line_no: usize,
+        line: &str,
+        text: &str,
+    ) -> Result<usize, ParseError> {
+        let (name, offset) = match text.find(['+', '-']) {
+            Some(at) => {
+                let offset: usize = parse_number(line_no, line, &text[at + 1..])?;
+                (&text[..at], Some((&text[at..at + 1], offset)))
+            }
+            None => (text, None),
+        };
+        let address = *self
+            .labels
+            .get(name)
+            .ok_or_else(|| ParseError::at(line_no, line, name, "unknown label"))?;
+        let out_of_range = || ParseError::at(line_no, line, text, "address out of range");
+        let address = match offset {
+            Some(("+", offset)) => address.checked_add(offset).ok_or_else(out_of_range)?,
+            Some((_, offset)) => address.checked_sub(offset).ok_or_else(|| {
+                ParseError::at(line_no, line, text, "before the first instruction")
+            })?,
+            None => address,
+        };
+        if W::from_index(address).to_index() != address {
+            return Err(out_of_range());
+        }
+        Ok(address)
+    }
+
+    /// An operand the opcode uses as `operand`.
+    fn operand<W: Word>(
+        &self,
+        line_no: usize,
+        line: &str,
+        text: &str,
+        operand: Operand,
+    ) -> Result<W, ParseError> {
+        match operand {
+            Operand::Register => Ok(W::from_index(self.register(line_no, line, text)?)),
+            _ if text == "_" && operand == Operand::Ignored => Ok(W::default()),
+            _ if text.starts_with(|c: char| c.is_ascii_digit()) => {
+                parse_number(line_no, line, text)
+            }
+            _ => Ok(W::from_index(self.address::<W>(line_no, line, text)?)),
+        }
+    }
+
+    /// The instruction on a line, which is an opcode and three operands or a `jmp`.
+    fn instruction<W: Word>(
+        &self,
+        line_no: usize,
+        line: &str,
+        words: &[&str],
+    ) -> Result<Instruction<W>, ParseError> {
+        match words {
+            ["jmp", target] => {
+                let ip_reg = self.ip_reg.ok_or_else(|| {
+                    ParseError::at(line_no, line, "jmp", "jmp needs the ip to be bound")
+                })?;
+                let address = self.address::<W>(line_no, line, target)?;
+                let before = address.checked_sub(1).ok_or_else(|| {
+                    ParseError::at(line_no, line, target, "can't jmp to the first instruction")
+                })?;
+                Ok(Instruction::new(
+                    Opcode::seti,
+                    W::from_index(before),
+                    W::default(),
+                    W::from_index(ip_reg),
+                ))
+            }
+            [mnemonic, a, b, c] => {
+                let op = Opcode::from_mnemonic(mnemonic)
+                    .ok_or_else(|| ParseError::at(line_no, line, mnemonic, "unknown opcode"))?;
+                let (a_operand, b_operand) = op.operands();
+                Ok(Instruction::new(
+                    op,
+                    self.operand(line_no, line, a, a_operand)?,
+                    self.operand(line_no, line, b, b_operand)?,
+                    self.operand(line_no, line, c, Operand::Register)?,
+                ))
+            }
+            _ => {
+                let message = "expected an opcode and three operands, or jmp and a label";
+                Err(ParseError::line(line_no, line, message))
+            }
+        }
+    }
+}
+
+impl<W: Word> Program<W> {
+    /// Assembles a program for a machine with `registers` registers, from the format at the
+    /// top of this module.
+    pub fn assemble(input: &str, registers: usize) -> Result<Program<W>, ParseError> {
+        let mut symbols = Symbols::collect(input, registers)?;
+        let mut instructions = Vec::new();
+        for (line_no, line) in numbered_lines(input) {
+            let (_, words) = split_line(line);
+            match words.as_slice() {
+                [] | ["#reg", ..] => {}
+                ["#ip", reg] => {
+                    if !instructions.is_empty() || symbols.ip_reg.is_some() {
+                        let message = "the ip can only be bound once, before the instructions";
+                        return Err(ParseError::line(line_no, line, message));
+                    }
+                    symbols.ip_reg = Some(symbols.register(line_no, line, reg)?);
+                }
+                words => instructions.push(symbols.instruction(line_no, line, words)?),
+            }
+        }
+        Ok(Program::new(symbols.ip_reg, instructions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Machine;
+
+    #[test]
+    fn test_assemble() {
+        // The example from day 19, with labels.
+        let input = "#reg x 1
+#reg y 2
+#ip 0
+        seti 5 _ x
+        seti 6 _ y
+        addi ip 1 ip    ; skip the next one
+        addr x y r3
+        setr x _ ip
+        seti 8 0 4
+end:    seti 9 _ r5
+";
+        let plain = "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5
+";
+        let program: Program<u64> = Program::assemble(input, 6).unwrap();
+        assert_eq!(Program::parse(plain, 6).unwrap(), program);
+        assert_eq!(plain, program.to_string());
+    }
+
+    #[test]
+    fn test_labels() {
+        // Doubles r1 from 3 until it's over 100, then records where it ended in r3.
+        let input = "#reg n 1
+#ip 4
+start:  seti 3 _ n
+again:  muli n 2 n
+        gtri n 100 r2
+        addr r2 ip ip
+        jmp again
+        seti done+1 _ r3
+done:
+";
+        let program: Program<u64> = Program::assemble(input, 6).unwrap();
+        assert_eq!(
+            Instruction::new(Opcode::seti, 0, 0, 4),
+            program.instructions()[4]
+        );
+        assert_eq!(
+            Instruction::new(Opcode::seti, 7, 0, 3),
+            program.instructions()[5]
+        );
+        let mut machine: Machine<u64, 6> = Machine::new(program.clone()).unwrap();
+        machine.run().unwrap();
+        assert_eq!([0, 192, 1, 7, 5, 0], *machine.registers());
+
+        // Writing it out and reading it back in gives the same program.
+        assert_eq!(program, Program::parse(&program.to_string(), 6).unwrap());
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let err = Program::<u64>::assemble("jmp nowhere", 6).unwrap_err();
+        assert_eq!(
+            "1:1: jmp needs the ip to be bound: \"jmp\"",
+            err.to_string()
+        );
+        let err = Program::<u64>::assemble("#ip 1\njmp nowhere", 6).unwrap_err();
+        assert_eq!("2:5: unknown label: \"nowhere\"", err.to_string());
+        let err = Program::<u64>::assemble("#ip 1\nstart: jmp start", 6).unwrap_err();
+        assert_eq!(
+            "2:12: can't jmp to the first instruction: \"start\"",
+            err.to_string()
+        );
+        let err = Program::<u64>::assemble("a: seti 0 0 1\na: seti 0 0 1", 6).unwrap_err();
+        assert_eq!("2:1: the label is already used: \"a\"", err.to_string());
+        let err = Program::<u64>::assemble("#reg r2 1", 6).unwrap_err();
+        assert_eq!("1:6: not a name: \"r2\"", err.to_string());
+        let err = Program::<u64>::assemble("addr x 1 2", 6).unwrap_err();
+        assert_eq!("1:6: not a register: \"x\"", err.to_string());
+        let err = Program::<u64>::assemble("addi r6 1 2", 6).unwrap_err();
+        assert_eq!("1:6: there are only 6 registers: \"r6\"", err.to_string());
+        let err = Program::<u64>::assemble("a: seti a-1 0 2", 6).unwrap_err();
+        assert_eq!(
+            "1:9: before the first instruction: \"a-1\"",
+            err.to_string()
+        );
+        let input = "seti 0 0 1\na: seti a+18446744073709551615 0 2";
+        let err = Program::<u64>::assemble(input, 6).unwrap_err();
+        assert_eq!(
+            "2:9: address out of range: \"a+18446744073709551615\"",
+            err.to_string()
+        );
+        // 256 doesn't fit in a u8.
+        let input = "seti 0 0 1\n".repeat(256) + "end: seti end 0 1";
+        let err = Program::<u8>::assemble(&input, 6).unwrap_err();
+        assert_eq!("257:11: address out of range: \"end\"", err.to_string());
+        let input = "#ip 0\n".to_string() + &"seti 0 0 1\n".repeat(256) + "end: jmp end";
+        let err = Program::<u8>::assemble(&input, 6).unwrap_err();
+        assert_eq!("258:10: address out of range: \"end\"", err.to_string());
+        let err = Program::<u64>::assemble("#def x 1", 6).unwrap_err();
+        assert_eq!("1:1: unknown directive: \"#def\"", err.to_string());
+    }
+}
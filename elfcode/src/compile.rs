@@ -0,0 +1,443 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Turning a program into something quicker to run than instructions.
+//!
+//! `Compiled` works out what each instruction does once, up front, so running it doesn't have to
+//! look at the opcode or the operands again, and overflows and faults the way a `Machine` does.
+//! `to_rust` goes further and writes the program out as Rust, with a function for each run of
+//! instructions that doesn't jump.
+
+use crate::{Fault, FaultKind, Instruction, Opcode, Operand, Overflow, Program, Word};
+use std::{collections::BTreeSet, fmt::Write};
+
+/// One instruction, with its operands already looked up.  It leaves the registers alone if it
+/// faults.
+type Op<W, const N: usize> = Box<dyn Fn(&mut [W; N]) -> Result<(), FaultKind>>;
+
+/// The closure that runs `ins`, doing `overflow` when an `add` or `mul` doesn't fit.
+fn op<W: Word + 'static, const N: usize>(ins: &Instruction<W>, overflow: Overflow) -> Op<W, N> {
+    let (a, b) = (ins.a, ins.b);
+    let (ra, rb, c) = (a.to_index(), b.to_index(), ins.c.to_index());
+    // Reading a register that isn't there faults every time, before anything else happens.
+    let (a_kind, b_kind) = ins.op.operands();
+    for (kind, register) in [(a_kind, ra), (b_kind, rb)] {
+        if kind == Operand::Register && register >= N {
+            return Box::new(move |_| Err(FaultKind::NoRegister(register)));
+        }
+    }
+    macro_rules! set {
+        ($r:ident => $value:expr) => {
+            Box::new(move |$r: &mut [W; N]| {
+                let value = $value;
+                *$r.get_mut(c).ok_or(FaultKind::NoRegister(c))? = value;
+                Ok(())
+            })
+        };
+    }
+    match ins.op {
+        Opcode::addr => set!(r => overflow.apply(r[ra].overflowing_add(r[rb]))?),
+        Opcode::addi => set!(r => overflow.apply(r[ra].overflowing_add(b))?),
+        Opcode::mulr => set!(r => overflow.apply(r[ra].overflowing_mul(r[rb]))?),
+        Opcode::muli => set!(r => overflow.apply(r[ra].overflowing_mul(b))?),
+        Opcode::banr => set!(r => r[ra] & r[rb]),
+        Opcode::bani => set!(r => r[ra] & b),
+        Opcode::borr => set!(r => r[ra] | r[rb]),
+        Opcode::bori => set!(r => r[ra] | b),
+        Opcode::setr => set!(r => r[ra]),
+        Opcode::seti => set!(_r => a),
+        Opcode::gtir => set!(r => W::from(a > r[rb])),
+        Opcode::gtri => set!(r => W::from(r[ra] > b)),
+        Opcode::gtrr => set!(r => W::from(r[ra] > r[rb])),
+        Opcode::eqir => set!(r => W::from(a == r[rb])),
+        Opcode::eqri => set!(r => W::from(r[ra] == b)),
+        Opcode::eqrr => set!(r => W::from(r[ra] == r[rb])),
+    }
+}
+
+/// A program as closures, one for each instruction.
+pub struct Compiled<W, const N: usize> {
+    ip_reg: Option<usize>,
+    ops: Vec<Op<W, N>>,
+}
+
+impl<W: Word + 'static, const N: usize> Compiled<W, N> {
+    /// Compiles `program`, to stop with a fault if an `add` or `mul` overflows.  Its
+    /// macro-instructions run in place of the loops they start, like they do on a `Machine`.
+    pub fn new(program: &Program<W>) -> Result<Compiled<W, N>, Fault> {
+        Compiled::with_overflow(program, Overflow::default())
+    }
+
+    /// Compiles `program`, to do `overflow` when an `add` or `mul` doesn't fit.
+    pub fn with_overflow(
+        program: &Program<W>,
+        overflow: Overflow,
+    ) -> Result<Compiled<W, N>, Fault> {
+        let ip_reg = program.ip_reg();
+        if let Some(ip_reg) = ip_reg.filter(|&ip_reg| ip_reg >= N) {
+            return Err(Fault {
+                ip: 0,
+                kind: FaultKind::NoIpRegister(ip_reg),
+            });
+        }
+        let mut ops: Vec<Op<W, N>> = program
+            .instructions()
+            .iter()
+            .map(|ins| op(ins, overflow))
+            .collect();
+        if let Some(ip_reg) = ip_reg {
+            for found in program.macros() {
+                let (idiom, exit) = (found.idiom, W::from_index(found.exit - 1));
+                // The loop runs an instruction at a time when the macro can't do it at once.
+                let head = op(&program.instructions()[found.start], overflow);
+                ops[found.start] = Box::new(move |r| {
+                    if !idiom.apply(r, overflow)? {
+                        return head(r);
+                    }
+                    r[ip_reg] = exit;
+                    Ok(())
+                });
+            }
+        }
+        Ok(Compiled { ip_reg, ops })
+    }
+
+    /// Runs the program from the start with `registers` until it halts, and returns how many
+    /// instructions that took.  If an instruction can't run, it stops there with the registers
+    /// as they were before it, and returns the fault.
+    pub fn run(&self, registers: &mut [W; N]) -> Result<usize, Fault> {
+        let mut ip = 0;
+        let mut steps = 0;
+        while let Some(op) = self.ops.get(ip) {
+            match self.ip_reg {
+                Some(ip_reg) => {
+                    let before = registers[ip_reg];
+                    registers[ip_reg] = W::from_index(ip);
+                    if let Err(kind) = op(registers) {
+                        registers[ip_reg] = before;
+                        return Err(Fault { ip, kind });
+                    }
+                    // Pointing as far as it goes leaves the program.
+                    ip = registers[ip_reg]
+                        .to_index()
+                        .checked_add(1)
+                        .unwrap_or(self.ops.len());
+                }
+                None => {
+                    op(registers).map_err(|kind| Fault { ip, kind })?;
+                    ip += 1;
+                }
+            }
+            steps += 1;
+        }
+        Ok(steps)
+    }
+}
+
+/// `ins`, at `at`, as a Rust assignment to the registers `r`.  An `add` or `mul` does
+/// `overflow` when it doesn't fit, and trapping panics with the fault.
+fn statement<W: Word>(ins: &Instruction<W>, at: usize, overflow: Overflow) -> String {
+    let (a_kind, b_kind) = ins.op.operands();
+    let operand = |kind, value: W| match kind {
+        Operand::Register => format!("r[{}]", value),
+        _ => value.to_string(),
+    };
+    let (a, b) = (operand(a_kind, ins.a), operand(b_kind, ins.b));
+    // The first operand of an add or mul is always a register, so it has a type to call on.
+    let arithmetic = |op| match overflow {
+        Overflow::Wrapping => format!("{}.wrapping_{}({})", a, op, b),
+        Overflow::Saturating => format!("{}.saturating_{}({})", a, op, b),
+        Overflow::Trapping => format!(
+            "{}.checked_{}({}).expect(\"instruction {} overflowed\")",
+            a, op, b, at
+        ),
+    };
+    let value = match ins.op {
+        Opcode::addr | Opcode::addi => arithmetic("add"),
+        Opcode::mulr | Opcode::muli => arithmetic("mul"),
+        Opcode::banr | Opcode::bani => format!("{} & {}", a, b),
+        Opcode::borr | Opcode::bori => format!("{} | {}", a, b),
+        Opcode::setr | Opcode::seti => a,
+        Opcode::gtir | Opcode::gtri | Opcode::gtrr => format!("({} > {}) as _", a, b),
+        Opcode::eqir | Opcode::eqri | Opcode::eqrr => format!("({} == {}) as _", a, b),
+    };
+    format!("r[{}] = {}", ins.c, value)
+}
+
+/// Whether `ins` uses register `reg`.
+fn reads<W: Word>(ins: &Instruction<W>, reg: usize) -> bool {
+    let (a_kind, b_kind) = ins.op.operands();
+    (a_kind == Operand::Register && ins.a.to_index() == reg)
+        || (b_kind == Operand::Register && ins.b.to_index() == reg)
+}
+
+/// Where each basic block starts: the start, the places jumps are known to go, and after every
+/// jump.  Jumps that add a register other than a comparison to the ip can go anywhere, and the
+/// dispatcher runs those a step at a time until they get back to the start of a block.
+fn leaders<W: Word>(instructions: &[Instruction<W>], ip_reg: Option<usize>) -> BTreeSet<usize> {
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+    let ip_reg = match ip_reg {
+        Some(ip_reg) => ip_reg,
+        None => return leaders,
+    };
+    for (at, ins) in instructions.iter().enumerate() {
+        if ins.c.to_index() != ip_reg {
+            continue;
+        }
+        leaders.insert(at + 1);
+        match ins.op {
+            Opcode::seti => {
+                leaders.extend(ins.a.to_index().checked_add(1));
+            }
+            Opcode::addi if ins.a.to_index() == ip_reg => {
+                leaders.extend(
+                    at.checked_add(ins.b.to_index())
+                        .and_then(|to| to.checked_add(1)),
+                );
+            }
+            Opcode::addr => {
+                // Adding a comparison either skips the next instruction or it doesn't.
+                leaders.insert(at + 2);
+            }
+            _ => {}
+        }
+    }
+    leaders.retain(|&leader| leader < instructions.len());
+    leaders
+}
+
+/// Writes the program as a standalone Rust program, with a function for each basic block.  It
+/// runs with r0 set to the number it's given, and prints the registers when it halts.  An `add`
+/// or `mul` that doesn't fit does `overflow`, like it does on a `Machine`, and trapping panics.
+/// The macro-instructions aren't in it; the compiler is left to make the loops quick.
+pub fn to_rust<W: Word, const N: usize>(program: &Program<W>, overflow: Overflow) -> String {
+    let instructions = program.instructions();
+    // Where a jump goes to, from the ip register.  Pointing as far as it goes leaves the program.
+    let jump = |ip_reg| {
+        format!(
+            "(r[{}] as usize).checked_add(1).unwrap_or({})",
+            ip_reg,
+            instructions.len()
+        )
+    };
+    let ip_reg = program.ip_reg();
+    let word = std::any::type_name::<W>();
+    let leaders = leaders(instructions, ip_reg);
+    let ends: Vec<usize> = leaders
+        .iter()
+        .skip(1)
+        .cloned()
+        .chain(Some(instructions.len()))
+        .collect();
+
+    let mut out = String::new();
+    writeln!(out, "// An ElfCode program, compiled to Rust by elfcode.\n").unwrap();
+    for (&start, &end) in leaders.iter().zip(ends.iter()) {
+        writeln!(out, "/// Instructions {} to {}.", start, end - 1).unwrap();
+        writeln!(
+            out,
+            "fn block_{}(r: &mut [{}; {}]) -> usize {{",
+            start, word, N
+        )
+        .unwrap();
+        let mut jumped = false;
+        for (at, ins) in instructions.iter().enumerate().take(end).skip(start) {
+            if let Some(ip_reg) = ip_reg {
+                if reads(ins, ip_reg) {
+                    writeln!(out, "    r[{}] = {};", ip_reg, at).unwrap();
+                }
+                jumped = ins.c.to_index() == ip_reg;
+            }
+            writeln!(out, "    {};", statement(ins, at, overflow)).unwrap();
+        }
+        match ip_reg {
+            Some(ip_reg) if jumped => writeln!(out, "    {}", jump(ip_reg)).unwrap(),
+            Some(ip_reg) => {
+                writeln!(out, "    r[{}] = {};", ip_reg, end - 1).unwrap();
+                writeln!(out, "    {}", end).unwrap();
+            }
+            None => writeln!(out, "    {}", end).unwrap(),
+        }
+        writeln!(out, "}}\n").unwrap();
+    }
+
+    writeln!(
+        out,
+        "/// Runs one instruction, for jumps into the middle of a block."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "fn step(r: &mut [{}; {}], ip: usize) -> usize {{",
+        word, N
+    )
+    .unwrap();
+    if let Some(ip_reg) = ip_reg {
+        writeln!(out, "    r[{}] = ip as {};", ip_reg, word).unwrap();
+    }
+    writeln!(out, "    match ip {{").unwrap();
+    for (at, ins) in instructions.iter().enumerate() {
+        writeln!(out, "        {} => {},", at, statement(ins, at, overflow)).unwrap();
+    }
+    writeln!(out, "        _ => unreachable!(),\n    }}").unwrap();
+    match ip_reg {
+        Some(ip_reg) => writeln!(out, "    {}\n}}\n", jump(ip_reg)).unwrap(),
+        None => writeln!(out, "    ip + 1\n}}\n").unwrap(),
+    }
+
+    writeln!(out, "pub fn run(r: &mut [{}; {}]) {{", word, N).unwrap();
+    writeln!(out, "    let mut ip = 0;").unwrap();
+    writeln!(out, "    while ip < {} {{", instructions.len()).unwrap();
+    writeln!(out, "        ip = match ip {{").unwrap();
+    for start in &leaders {
+        writeln!(out, "            {} => block_{}(r),", start, start).unwrap();
+    }
+    writeln!(
+        out,
+        "            _ => step(r, ip),\n        }};\n    }}\n}}\n"
+    )
+    .unwrap();
+
+    writeln!(out, "fn main() {{").unwrap();
+    writeln!(out, "    let mut r = [0; {}];", N).unwrap();
+    writeln!(out, "    if let Some(r0) = std::env::args().nth(1) {{").unwrap();
+    writeln!(
+        out,
+        "        r[0] = r0.parse().expect(\"r0 should be a number\");"
+    )
+    .unwrap();
+    writeln!(out, "    }}\n    run(&mut r);").unwrap();
+    writeln!(out, "    println!(\"{{:?}}\", r);\n}}").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Machine;
+
+    const DAY19: &str = include_str!("../../day19/input.txt");
+    const DAY21: &str = include_str!("../../day21/input.txt");
+
+    #[test]
+    fn test_compiled() {
+        for (input, r0) in [(DAY19, 0), (DAY21, 11840402)] {
+            let program: Program<u64> = Program::parse(input, 6).unwrap();
+            let mut machine: Machine<u64, 6> = Machine::new(program.clone()).unwrap();
+            machine.registers_mut()[0] = r0;
+            let mut registers = *machine.registers();
+            let steps = machine.run().unwrap();
+
+            let compiled = Compiled::new(&program).unwrap();
+            assert_eq!(Ok(steps), compiled.run(&mut registers));
+            assert_eq!(machine.registers(), &registers);
+
+            let mut registers = [r0, 0, 0, 0, 0, 0];
+            let optimized: Compiled<u64, 6> = Compiled::new(&program.optimize()).unwrap();
+            optimized.run(&mut registers).unwrap();
+            assert_eq!(machine.registers()[0], registers[0]);
+        }
+    }
+
+    #[test]
+    fn test_compiled_faults() {
+        // The same faults as the machine, where it has them.
+        let input = "seti 200 0 0\nmuli 0 2 1\naddr 0 6 2";
+        let program: Program<u8> = Program::parse(input, 7).unwrap();
+        for overflow in [Overflow::Trapping, Overflow::Wrapping, Overflow::Saturating] {
+            let mut machine: Machine<u8, 4> = Machine::new(program.clone())
+                .unwrap()
+                .with_overflow(overflow);
+            let expected = machine.run();
+            let compiled: Compiled<u8, 4> = Compiled::with_overflow(&program, overflow).unwrap();
+            let mut registers = [0; 4];
+            assert_eq!(expected.map(|_| 0), compiled.run(&mut registers).map(|_| 0));
+            assert_eq!(machine.registers(), &registers);
+        }
+
+        // The macros too, where dividing overflows on the way to the answer.
+        let input = "#ip 3\nseti 0 3 1\naddi 1 1 5\nmuli 5 200 5\ngtrr 5 2 5\naddr 5 3 3\n\
+                     addi 3 1 3\nseti 8 9 3\naddi 1 1 1\nseti 0 4 3\nsetr 1 9 2";
+        let program: Program<u8> = Program::parse(input, 6).unwrap().optimize();
+        assert_eq!(1, program.macros().count());
+        for overflow in [Overflow::Trapping, Overflow::Wrapping, Overflow::Saturating] {
+            for n in [150, 210] {
+                let mut machine: Machine<u8, 6> = Machine::new(program.clone())
+                    .unwrap()
+                    .with_overflow(overflow);
+                machine.registers_mut()[2] = n;
+                let mut registers = *machine.registers();
+                let expected = machine.run().map(|_| ());
+                let compiled: Compiled<u8, 6> =
+                    Compiled::with_overflow(&program, overflow).unwrap();
+                assert_eq!(expected, compiled.run(&mut registers).map(|_| ()));
+                assert_eq!(machine.registers(), &registers);
+            }
+        }
+
+        // Pointing the ip as far as it goes halts the program, without overflowing the leaders.
+        let program: Program<u64> =
+            Program::parse("#ip 0\nseti 18446744073709551615 0 0", 6).unwrap();
+        let compiled: Compiled<u64, 6> = Compiled::new(&program).unwrap();
+        assert_eq!(Ok(1), compiled.run(&mut [0; 6]));
+        let rust = to_rust::<u64, 6>(&program, Overflow::Trapping);
+        assert!(
+            rust.contains("(r[0] as usize).checked_add(1).unwrap_or(1)"),
+            "{}",
+            rust
+        );
+    }
+
+    #[test]
+    fn test_to_rust() {
+        let program: Program<u16> =
+            Program::parse("#ip 1\nseti 5 0 0\ngtri 0 3 2\naddr 2 1 1\naddi 0 1 0", 4).unwrap();
+        let rust = to_rust::<u16, 4>(&program, Overflow::Wrapping);
+        let expected = "\
+// An ElfCode program, compiled to Rust by elfcode.
+
+/// Instructions 0 to 2.
+fn block_0(r: &mut [u16; 4]) -> usize {
+    r[0] = 5;
+    r[2] = (r[0] > 3) as _;
+    r[1] = 2;
+    r[1] = r[2].wrapping_add(r[1]);
+    (r[1] as usize).checked_add(1).unwrap_or(4)
+}
+
+/// Instructions 3 to 3.
+fn block_3(r: &mut [u16; 4]) -> usize {
+    r[0] = r[0].wrapping_add(1);
+    r[1] = 3;
+    4
+}
+
+/// Runs one instruction, for jumps into the middle of a block.
+fn step(r: &mut [u16; 4], ip: usize) -> usize {
+    r[1] = ip as u16;
+    match ip {
+        0 => r[0] = 5,
+        1 => r[2] = (r[0] > 3) as _,
+        2 => r[1] = r[2].wrapping_add(r[1]),
+        3 => r[0] = r[0].wrapping_add(1),
+        _ => unreachable!(),
+    }
+    (r[1] as usize).checked_add(1).unwrap_or(4)
+}
+";
+        assert!(rust.starts_with(expected), "{}", rust);
+        assert!(rust.contains("            3 => block_3(r),\n            _ => step(r, ip),"));
+
+        // The other modes overflow like the machine does.
+        let rust = to_rust::<u16, 4>(&program, Overflow::Saturating);
+        assert!(
+            rust.contains("    r[0] = r[0].saturating_add(1);\n"),
+            "{}",
+            rust
+        );
+        let rust = to_rust::<u16, 4>(&program, Overflow::Trapping);
+        let trap = "    r[0] = r[0].checked_add(1).expect(\"instruction 3 overflowed\");\n";
+        assert!(rust.contains(trap), "{}", rust);
+    }
+}
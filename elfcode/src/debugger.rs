@@ -0,0 +1,507 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Running a program a bit at a time, to see what it's up to.
+//!
+//! The debugger stops at breakpoints, which are instructions, and at watchpoints, which are
+//! registers being read or written.  It remembers the registers before each step so it can go
+//! back.  `command` runs the commands from the `elfcode debug` prompt, which `help` lists.
+
+use crate::{disassemble, Fault, Machine, Operand, Word};
+use log::trace;
+use std::{
+    collections::{BTreeSet, VecDeque},
+    fmt,
+};
+
+/// What a watchpoint watches a register for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Watch<W> {
+    /// An instruction reads it.
+    Read,
+    /// An instruction writes it.
+    Write,
+    /// An instruction writes this value to it.
+    Becomes(W),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchpoint<W> {
+    pub register: usize,
+    pub watch: Watch<W>,
+}
+
+impl<W: fmt::Display> fmt::Display for Watchpoint<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.watch {
+            Watch::Read => write!(f, "r{} read", self.register),
+            Watch::Write => write!(f, "r{} written", self.register),
+            Watch::Becomes(value) => write!(f, "r{} becomes {}", self.register, value),
+        }
+    }
+}
+
+/// Why the debugger stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// About to run the instruction with a breakpoint on it.
+    Breakpoint(usize),
+    /// The instruction at the first number set off the watchpoint at the second.
+    Watchpoint(usize, usize),
+    /// The next instruction can't run.
+    Fault(Fault),
+    Halted,
+}
+
+/// A machine, with the things to stop it at and where it's been.
+pub struct Debugger<W, const N: usize> {
+    machine: Machine<W, N>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: Vec<Watchpoint<W>>,
+    /// The ip and registers before each step, the latest last.
+    history: VecDeque<(usize, [W; N])>,
+    history_limit: usize,
+    steps: usize,
+}
+
+impl<W: Word, const N: usize> Debugger<W, N> {
+    /// A debugger for `machine` which can go back 1000 steps.
+    pub fn new(machine: Machine<W, N>) -> Debugger<W, N> {
+        Debugger {
+            machine,
+            breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            history: VecDeque::new(),
+            history_limit: 1000,
+            steps: 0,
+        }
+    }
+
+    /// Remembers `limit` steps to go back, or none at all for a faster run.
+    pub fn with_history(mut self, limit: usize) -> Debugger<W, N> {
+        self.history_limit = limit;
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
+        self
+    }
+
+    pub fn machine(&self) -> &Machine<W, N> {
+        &self.machine
+    }
+
+    pub fn machine_mut(&mut self) -> &mut Machine<W, N> {
+        &mut self.machine
+    }
+
+    /// How many instructions have run, less any that were rewound.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn add_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.insert(ip);
+    }
+
+    /// Returns whether there was a breakpoint there.
+    pub fn remove_breakpoint(&mut self, ip: usize) -> bool {
+        self.breakpoints.remove(&ip)
+    }
+
+    /// Adds a watchpoint, and returns its number.
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint<W>) -> usize {
+        self.watchpoints.push(watchpoint);
+        self.watchpoints.len() - 1
+    }
+
+    /// Which watchpoint the next step sets off, going by what it reads and writes.  A
+    /// macro-instruction reads and writes everything its loop does, and becomes what it leaves.
+    fn watchpoint_hit(&self) -> Option<usize> {
+        let ins = self.machine.next_instruction()?;
+        let ip_reg = self.machine.program().ip_reg();
+        let mut after = *self.machine.registers();
+        if let Some(ip_reg) = ip_reg {
+            after[ip_reg] = W::from_index(self.machine.ip());
+        }
+        let overflow = self.machine.overflow();
+        // A macro that returns false runs the loop the slow way, starting with this instruction.
+        let applied = self.machine.next_macro().and_then(|found| {
+            match found.idiom.apply(&mut after, overflow) {
+                Ok(false) => None,
+                applied => Some((found, applied.is_ok())),
+            }
+        });
+        let (mut reads, mut writes) = (Vec::new(), Vec::new());
+        // If it faults, the watchpoints that look at the result don't go off.
+        let ran = match applied {
+            Some((found, ran)) => {
+                reads = found.idiom.reads();
+                writes = found.idiom.writes();
+                // The loop's jumps read and write the ip too.
+                if let Some(ip_reg) = ip_reg {
+                    after[ip_reg] = W::from_index(found.exit - 1);
+                    reads.push(ip_reg);
+                    writes.push(ip_reg);
+                }
+                ran
+            }
+            None => {
+                let (a_kind, b_kind) = ins.op.operands();
+                if a_kind == Operand::Register {
+                    reads.push(ins.a.to_index());
+                }
+                if b_kind == Operand::Register {
+                    reads.push(ins.b.to_index());
+                }
+                writes.push(ins.c.to_index());
+                ins.execute(&mut after, overflow).is_ok()
+            }
+        };
+        self.watchpoints.iter().position(|wp| match wp.watch {
+            Watch::Read => reads.contains(&wp.register),
+            Watch::Write => ran && writes.contains(&wp.register),
+            Watch::Becomes(value) => {
+                ran && writes.contains(&wp.register) && after[wp.register] == value
+            }
+        })
+    }
+
+    /// Runs one instruction.  Returns why it should stop there, if it should.
+    pub fn step(&mut self) -> Option<Stop> {
+        if self.machine.is_halted() {
+            return Some(Stop::Halted);
+        }
+        let ip = self.machine.ip();
+        let found = self.machine.next_macro().copied();
+        let watched = if self.watchpoints.is_empty() {
+            None
+        } else {
+            self.watchpoint_hit()
+        };
+        let before = *self.machine.registers();
+        if self.history_limit > 0 {
+            if self.history.len() == self.history_limit {
+                self.history.pop_front();
+            }
+            self.history.push_back((ip, before));
+        }
+        if let Err(fault) = self.machine.step() {
+            // Nothing happened, so there's nothing to go back over.
+            if self.history_limit > 0 {
+                self.history.pop_back();
+            }
+            return Some(Stop::Fault(fault));
+        }
+        self.steps += 1;
+        if log::log_enabled!(log::Level::Trace) {
+            let after = self.machine.registers();
+            match found {
+                Some(found) => trace!("ip={} {:?} {} {:?}", ip, before, found.idiom, after),
+                None => {
+                    let ins = self.machine.program().instructions()[ip];
+                    trace!("ip={} {:?} {} {:?}", ip, before, ins, after);
+                }
+            }
+        }
+        if let Some(watchpoint) = watched {
+            return Some(Stop::Watchpoint(ip, watchpoint));
+        }
+        if self.machine.is_halted() {
+            return Some(Stop::Halted);
+        }
+        None
+    }
+
+    /// Runs until a breakpoint or a watchpoint, or the program halts.  It always runs at least
+    /// one instruction, so it doesn't stop at the breakpoint it's already at.
+    pub fn cont(&mut self) -> Stop {
+        loop {
+            if let Some(stop) = self.step() {
+                return stop;
+            }
+            if self.breakpoints.contains(&self.machine.ip()) {
+                return Stop::Breakpoint(self.machine.ip());
+            }
+        }
+    }
+
+    /// Goes back up to `steps` instructions, and returns how many it went back.
+    pub fn rewind(&mut self, steps: usize) -> usize {
+        let mut rewound = 0;
+        while rewound < steps {
+            let (ip, registers) = match self.history.pop_back() {
+                Some(state) => state,
+                None => break,
+            };
+            self.machine.set_ip(ip);
+            *self.machine.registers_mut() = registers;
+            self.steps -= 1;
+            rewound += 1;
+        }
+        rewound
+    }
+
+    /// Where the machine is and what's in the registers.
+    pub fn registers(&self) -> String {
+        let next = match self.machine.next_instruction() {
+            Some(ins) => ins.to_string(),
+            None => "halted".to_string(),
+        };
+        format!(
+            "ip={} {:?} next: {} ({} steps)",
+            self.machine.ip(),
+            self.machine.registers(),
+            next,
+            self.steps
+        )
+    }
+
+    fn describe(&self, stop: Stop) -> String {
+        let stopped = match stop {
+            Stop::Breakpoint(ip) => format!("Breakpoint at {}", ip),
+            Stop::Watchpoint(ip, idx) => {
+                format!("Watchpoint {} ({}) at {}", idx, self.watchpoints[idx], ip)
+            }
+            Stop::Fault(fault) => format!("Fault: {}", fault),
+            Stop::Halted => "Halted".to_string(),
+        };
+        format!("{}\n{}", stopped, self.registers())
+    }
+
+    /// The register named like `r3`.
+    fn register(&self, name: &str) -> Option<usize> {
+        let register = name.strip_prefix('r')?.parse().ok()?;
+        if register < N {
+            Some(register)
+        } else {
+            None
+        }
+    }
+
+    /// Runs a command from the prompt, and returns what to say about it.
+    pub fn command(&mut self, line: &str) -> String {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |word: &str| word.parse::<usize>().ok();
+        match words.as_slice() {
+            ["s"] | ["step"] => self.command("step 1"),
+            ["s", n] | ["step", n] => match number(n) {
+                Some(n) => {
+                    let stop = (0..n).find_map(|_| self.step());
+                    match stop {
+                        Some(stop) => self.describe(stop),
+                        None => self.registers(),
+                    }
+                }
+                None => format!("Can't step {} times", n),
+            },
+            ["c"] | ["continue"] => {
+                let stop = self.cont();
+                self.describe(stop)
+            }
+            ["b"] | ["break"] => format!("Breakpoints: {:?}", self.breakpoints),
+            ["b", ip] | ["break", ip] => match number(ip) {
+                Some(ip) => {
+                    self.add_breakpoint(ip);
+                    format!("Breakpoint at {}", ip)
+                }
+                None => format!("There's no instruction {}", ip),
+            },
+            ["d", ip] | ["delete", ip] => match number(ip) {
+                Some(ip) if self.remove_breakpoint(ip) => format!("Deleted breakpoint at {}", ip),
+                _ => format!("There's no breakpoint at {}", ip),
+            },
+            ["w", reg, rest @ ..] | ["watch", reg, rest @ ..] => {
+                let register = match self.register(reg) {
+                    Some(register) => register,
+                    None => return format!("There's no register {}", reg),
+                };
+                let watch = match rest {
+                    ["read"] => Watch::Read,
+                    ["write"] => Watch::Write,
+                    ["==", value] => match W::from_str(value) {
+                        Ok(value) => Watch::Becomes(value),
+                        Err(_) => return format!("{} isn't a value", value),
+                    },
+                    _ => return "Watch for read, write or == VALUE".to_string(),
+                };
+                let watchpoint = Watchpoint { register, watch };
+                let idx = self.add_watchpoint(watchpoint);
+                format!("Watchpoint {}: {}", idx, watchpoint)
+            }
+            ["r"] | ["regs"] => self.registers(),
+            ["set", reg, value] => match (self.register(reg), W::from_str(value)) {
+                (Some(register), Ok(value)) => {
+                    self.machine.registers_mut()[register] = value;
+                    self.registers()
+                }
+                (None, _) => format!("There's no register {}", reg),
+                (_, Err(_)) => format!("{} isn't a value", value),
+            },
+            ["back"] => self.command("back 1"),
+            ["back", n] => match number(n) {
+                Some(n) => {
+                    let rewound = self.rewind(n);
+                    format!("Went back {} steps\n{}", rewound, self.registers())
+                }
+                None => format!("Can't go back {} steps", n),
+            },
+            ["l"] | ["list"] => disassemble(self.machine.program()),
+            ["h"] | ["help"] => HELP.to_string(),
+            [] => String::new(),
+            _ => format!("Unknown command {:?}, try help", line.trim()),
+        }
+    }
+}
+
+const HELP: &str = "\
+step [N]             run one (or N) instructions
+continue             run until a breakpoint, a watchpoint or the end
+break [IP]           stop before running instruction IP, or list the breakpoints
+delete IP            remove the breakpoint at IP
+watch rN read        stop after an instruction reads register N
+watch rN write       stop after an instruction writes register N
+watch rN == VALUE    stop after an instruction sets register N to VALUE
+regs                 show the registers and the next instruction
+set rN VALUE         set register N
+back [N]             go back one (or N) instructions
+list                 list the program
+quit                 stop debugging";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Program;
+
+    fn debugger() -> Debugger<u64, 6> {
+        // The example from day 19.
+        let program = Program::parse(
+            "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5
+",
+            6,
+        )
+        .unwrap();
+        Debugger::new(Machine::new(program).unwrap())
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(4);
+        assert_eq!(Stop::Breakpoint(4), debugger.cont());
+        assert_eq!([3, 5, 6, 0, 0, 0], *debugger.machine().registers());
+        assert_eq!(Stop::Halted, debugger.cont());
+        assert_eq!([6, 5, 6, 0, 0, 9], *debugger.machine().registers());
+        assert_eq!(5, debugger.steps());
+        assert_eq!(Some(Stop::Halted), debugger.step());
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut debugger = debugger();
+        debugger.add_watchpoint(Watchpoint {
+            register: 2,
+            watch: Watch::Read,
+        });
+        debugger.add_watchpoint(Watchpoint {
+            register: 5,
+            watch: Watch::Becomes(9),
+        });
+        // addr 1 2 3 never runs, so nothing reads r2.
+        assert_eq!(Stop::Watchpoint(6, 1), debugger.cont());
+        assert_eq!(Stop::Halted, debugger.cont());
+    }
+
+    #[test]
+    fn test_macro_watchpoints() {
+        // Dividing 1000 by 256 with a macro-instruction at 1, and leaving the quotient in r2.
+        let program = Program::parse(
+            "#ip 3
+seti 0 3 1
+addi 1 1 5
+muli 5 256 5
+gtrr 5 2 5
+addr 5 3 3
+addi 3 1 3
+seti 8 9 3
+addi 1 1 1
+seti 0 4 3
+setr 1 9 2
+",
+            6,
+        )
+        .unwrap()
+        .optimize();
+        let mut machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        machine.registers_mut()[2] = 1000;
+        for watch in [
+            Watchpoint {
+                register: 2,
+                watch: Watch::Read,
+            },
+            Watchpoint {
+                register: 5,
+                watch: Watch::Write,
+            },
+            Watchpoint {
+                register: 1,
+                watch: Watch::Becomes(3),
+            },
+        ] {
+            let mut debugger = Debugger::new(machine.clone());
+            debugger.add_watchpoint(watch);
+            assert_eq!(Stop::Watchpoint(1, 0), debugger.cont(), "{}", watch);
+            assert_eq!(9, debugger.machine().ip());
+            assert_eq!(3, debugger.machine().registers()[1]);
+        }
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut debugger = debugger();
+        assert_eq!(Stop::Halted, debugger.cont());
+        assert_eq!(2, debugger.rewind(2));
+        assert_eq!(3, debugger.steps());
+        assert_eq!(4, debugger.machine().ip());
+        assert_eq!([3, 5, 6, 0, 0, 0], *debugger.machine().registers());
+        assert_eq!(3, debugger.rewind(10));
+        assert_eq!(0, debugger.machine().ip());
+
+        let mut debugger = debugger.with_history(1);
+        debugger.cont();
+        assert_eq!(1, debugger.rewind(2));
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut debugger = debugger();
+        assert_eq!("Breakpoint at 6", debugger.command("break 6"));
+        assert_eq!("Watchpoint 0: r1 read", debugger.command("w r1 read"));
+        assert_eq!(
+            "ip=2 [1, 5, 6, 0, 0, 0] next: addi 0 1 0 (2 steps)",
+            debugger.command("step 2")
+        );
+        assert_eq!(
+            "Watchpoint 0 (r1 read) at 4\nip=6 [5, 5, 6, 0, 0, 0] next: seti 9 0 5 (4 steps)",
+            debugger.command("c")
+        );
+        assert_eq!(
+            "Went back 1 steps\nip=4 [3, 5, 6, 0, 0, 0] next: setr 1 0 0 (3 steps)",
+            debugger.command("back")
+        );
+        assert_eq!("There's no register r6", debugger.command("watch r6 read"));
+        assert_eq!("x isn't a value", debugger.command("watch r1 == x"));
+        assert_eq!("There's no breakpoint at 2", debugger.command("delete 2"));
+        assert_eq!(
+            "ip=4 [3, 5, 6, 7, 0, 0] next: setr 1 0 0 (3 steps)",
+            debugger.command("set r3 7")
+        );
+        assert!(debugger.command("frob").starts_with("Unknown command"));
+    }
+}
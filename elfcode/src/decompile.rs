@@ -0,0 +1,530 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Turns ElfCode back into something a person can read.
+//!
+//! First every instruction becomes a statement.  Reading the ip register gives the number of the
+//! instruction, and writing it is a jump, so `addi 4 16 4` at instruction 0 is `goto L17`.  A
+//! comparison followed by adding its result to the ip skips the next instruction if it's true,
+//! which becomes a conditional goto.  Then gotos that jump backwards become loops and gotos that
+//! jump forwards over a block become ifs, and whatever is left stays a labelled goto.
+
+use crate::{Opcode, Operand, Overflow, Program, Word};
+use std::{collections::BTreeSet, fmt};
+
+/// A value in the pseudocode.
+#[derive(Clone, Debug, PartialEq)]
+enum Expr<W> {
+    Reg(usize),
+    Value(W),
+    Binary(&'static str, Box<Expr<W>>, Box<Expr<W>>),
+}
+
+impl<W: Word> Expr<W> {
+    fn binary(op: &'static str, a: Expr<W>, b: Expr<W>) -> Expr<W> {
+        let expr = Expr::Binary(op, Box::new(a), Box::new(b));
+        match expr.eval() {
+            Some(value) => Expr::Value(value),
+            None => expr,
+        }
+    }
+
+    /// The value, if it doesn't depend on any registers and doesn't overflow.
+    fn eval(&self) -> Option<W> {
+        match self {
+            Expr::Reg(_) => None,
+            Expr::Value(v) => Some(*v),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval()?, b.eval()?);
+                Some(match *op {
+                    "+" => Overflow::Trapping.apply(a.overflowing_add(b)).ok()?,
+                    "*" => Overflow::Trapping.apply(a.overflowing_mul(b)).ok()?,
+                    "&" => a & b,
+                    "|" => a | b,
+                    ">" => W::from(a > b),
+                    "==" => W::from(a == b),
+                    _ => return None,
+                })
+            }
+        }
+    }
+
+    fn is_comparison(&self) -> bool {
+        matches!(self, Expr::Binary(">", _, _) | Expr::Binary("==", _, _))
+    }
+
+    fn uses(&self, reg: usize) -> bool {
+        match self {
+            Expr::Reg(r) => *r == reg,
+            Expr::Value(_) => false,
+            Expr::Binary(_, a, b) => a.uses(reg) || b.uses(reg),
+        }
+    }
+
+    /// The opposite condition.
+    fn negate(&self) -> Expr<W> {
+        match self {
+            Expr::Binary(op, a, b) => {
+                let op = match *op {
+                    ">" => "<=",
+                    "<=" => ">",
+                    "==" => "!=",
+                    "!=" => "==",
+                    _ => return Expr::binary("==", self.clone(), Expr::Value(W::default())),
+                };
+                Expr::Binary(op, a.clone(), b.clone())
+            }
+            _ => Expr::Binary(
+                "==",
+                Box::new(self.clone()),
+                Box::new(Expr::Value(W::default())),
+            ),
+        }
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for Expr<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Reg(r) => write!(f, "r{}", r),
+            Expr::Value(v) => write!(f, "{}", v),
+            Expr::Binary(op, a, b) => {
+                let side = |expr: &Expr<W>| match expr {
+                    Expr::Binary(..) => format!("({})", expr),
+                    _ => expr.to_string(),
+                };
+                write!(f, "{} {} {}", side(a), op, side(b))
+            }
+        }
+    }
+}
+
+/// What one instruction does.
+#[derive(Clone, Debug, PartialEq)]
+enum Stmt<W> {
+    Assign(usize, Expr<W>),
+    /// Jump to an instruction.  Past the end of the program is a halt.
+    Goto(usize),
+    /// Jump to an instruction if the condition isn't zero.
+    If(Expr<W>, usize),
+    /// Jump to wherever this says, plus one.
+    Jump(Expr<W>),
+}
+
+impl<W> Stmt<W> {
+    fn target(&self) -> Option<usize> {
+        match self {
+            Stmt::Goto(t) | Stmt::If(_, t) => Some(*t),
+            _ => None,
+        }
+    }
+}
+
+/// The statement for the instruction at `at`.
+fn statement<W: Word>(program: &Program<W>, at: usize) -> Stmt<W> {
+    let ip_reg = program.ip_reg();
+    let ins = &program.instructions()[at];
+    let operand = |x: W, kind: Operand| match kind {
+        Operand::Register if Some(x.to_index()) == ip_reg => Expr::Value(W::from_index(at)),
+        Operand::Register => Expr::Reg(x.to_index()),
+        Operand::Immediate | Operand::Ignored => Expr::Value(x),
+    };
+    let (a_kind, b_kind) = ins.op.operands();
+    let (a, b) = (operand(ins.a, a_kind), operand(ins.b, b_kind));
+    let expr = match ins.op {
+        Opcode::addr | Opcode::addi => Expr::binary("+", a, b),
+        Opcode::mulr | Opcode::muli => Expr::binary("*", a, b),
+        Opcode::banr | Opcode::bani => Expr::binary("&", a, b),
+        Opcode::borr | Opcode::bori => Expr::binary("|", a, b),
+        Opcode::setr | Opcode::seti => a,
+        Opcode::gtir | Opcode::gtri | Opcode::gtrr => Expr::binary(">", a, b),
+        Opcode::eqir | Opcode::eqri | Opcode::eqrr => Expr::binary("==", a, b),
+    };
+    if Some(ins.c.to_index()) != ip_reg {
+        return Stmt::Assign(ins.c.to_index(), expr);
+    }
+    // Pointing the ip as far as it goes leaves the program, like running off the end.
+    let end = program.instructions().len();
+    match expr.eval() {
+        Some(target) => Stmt::Goto(target.to_index().checked_add(1).unwrap_or(end)),
+        None => Stmt::Jump(expr),
+    }
+}
+
+/// All the statements, with jumps that skip an instruction when a comparison is true made into
+/// conditional gotos.  When the instruction they skip is a goto, that goes into the condition
+/// instead, and the goto becomes a jump to the next instruction which does nothing.
+fn statements<W: Word>(program: &Program<W>) -> Vec<Stmt<W>> {
+    let mut stmts: Vec<Stmt<W>> = (0..program.instructions().len())
+        .map(|at| statement(program, at))
+        .collect();
+    let targets: BTreeSet<usize> = stmts.iter().filter_map(Stmt::target).collect();
+    for at in 1..stmts.len() {
+        // Jumping here from somewhere else means the register might not be the comparison.
+        if targets.contains(&at) {
+            continue;
+        }
+        let skip = match (&stmts[at - 1], &stmts[at]) {
+            (Stmt::Assign(reg, cmp), Stmt::Jump(Expr::Binary("+", a, b)))
+                if cmp.is_comparison() =>
+            {
+                let here = Expr::Value(W::from_index(at));
+                let reg_expr = Expr::Reg(*reg);
+                if (**a == here && **b == reg_expr) || (**a == reg_expr && **b == here) {
+                    // The register still has the comparison in it if the comparison didn't use
+                    // it, so we can say what it was.
+                    Some(if cmp.uses(*reg) {
+                        reg_expr
+                    } else {
+                        cmp.clone()
+                    })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        if let Some(cond) = skip {
+            stmts[at] = match stmts.get(at + 1) {
+                Some(&Stmt::Goto(target)) if !targets.contains(&(at + 1)) => {
+                    stmts[at + 1] = Stmt::Goto(at + 2);
+                    Stmt::If(cond.negate(), target)
+                }
+                _ => Stmt::If(cond, at + 2),
+            };
+        }
+    }
+    stmts
+}
+
+/// Whether the statement at `at` just goes on to the next one.
+fn is_nop<W>(stmts: &[Stmt<W>], at: usize) -> bool {
+    matches!(stmts.get(at), Some(Stmt::Goto(target)) if *target == at + 1)
+}
+
+/// Whether anything outside `from..to` except `except` jumps into it.
+fn entered<W>(stmts: &[Stmt<W>], from: usize, to: usize, except: usize) -> bool {
+    stmts.iter().enumerate().any(|(at, stmt)| {
+        at != except
+            && (at < from || at >= to)
+            && stmt.target().is_some_and(|t| t >= from && t < to)
+    })
+}
+
+/// Pseudocode with structure.
+enum Node<W> {
+    Stmt(usize, Stmt<W>),
+    If(Expr<W>, Vec<Node<W>>, Vec<Node<W>>),
+    /// A loop from `head` which carries on at `exit`, going around again while the condition
+    /// holds, or forever if there isn't one.
+    Loop {
+        head: usize,
+        exit: usize,
+        body: Vec<Node<W>>,
+        cond: Option<Expr<W>>,
+    },
+}
+
+/// Where `continue` and `break` would go in the loop we're in.
+#[derive(Clone, Copy, Default)]
+struct LoopCtx {
+    head: Option<usize>,
+    exit: Option<usize>,
+}
+
+fn structure<W: Word>(stmts: &[Stmt<W>], start: usize, end: usize) -> Vec<Node<W>> {
+    let mut nodes = Vec::new();
+    let mut at = start;
+    while at < end {
+        // The last jump back to here makes a loop, as long as nothing jumps into the middle of it.
+        let back = (at..end).rev().find(|&j| stmts[j].target() == Some(at));
+        if let Some(last) = back.filter(|&last| !entered(stmts, at + 1, last + 1, at)) {
+            let cond = match &stmts[last] {
+                Stmt::If(cond, _) => Some(cond.clone()),
+                _ => None,
+            };
+            let mut exit = last + 1;
+            while is_nop(stmts, exit) {
+                exit += 1;
+            }
+            nodes.push(Node::Loop {
+                head: at,
+                exit,
+                body: structure(stmts, at, last),
+                cond,
+            });
+            at = last + 1;
+            continue;
+        }
+        if let Stmt::If(cond, then_end) = &stmts[at] {
+            let then_end = *then_end;
+            if at + 1 < then_end && then_end <= end && !entered(stmts, at + 1, then_end, at) {
+                // If the block ends by jumping over some more, that's the else.
+                if let Stmt::Goto(else_end) = stmts[then_end - 1] {
+                    if then_end < else_end
+                        && else_end <= end
+                        && at + 1 < then_end - 1
+                        && !entered(stmts, then_end, else_end, at)
+                    {
+                        nodes.push(Node::If(
+                            cond.negate(),
+                            structure(stmts, at + 1, then_end - 1),
+                            structure(stmts, then_end, else_end),
+                        ));
+                        at = else_end;
+                        continue;
+                    }
+                }
+                nodes.push(Node::If(
+                    cond.negate(),
+                    structure(stmts, at + 1, then_end),
+                    Vec::new(),
+                ));
+                at = then_end;
+                continue;
+            }
+        }
+        if !is_nop(stmts, at) {
+            nodes.push(Node::Stmt(at, stmts[at].clone()));
+        }
+        at += 1;
+    }
+    nodes
+}
+
+/// Lines of pseudocode, with the instruction they start at if it's one.
+struct Lines {
+    lines: Vec<(Option<usize>, usize, String)>,
+    /// Where the gotos that are left go.
+    labels: BTreeSet<usize>,
+    len: usize,
+}
+
+impl Lines {
+    fn jump(&mut self, target: usize, ctx: LoopCtx) -> String {
+        if target >= self.len {
+            "halt".to_string()
+        } else if Some(target) == ctx.head {
+            "continue".to_string()
+        } else if Some(target) == ctx.exit {
+            "break".to_string()
+        } else {
+            self.labels.insert(target);
+            format!("goto L{}", target)
+        }
+    }
+
+    fn stmt<W: Word>(&mut self, stmt: &Stmt<W>, ctx: LoopCtx) -> String {
+        match stmt {
+            Stmt::Assign(reg, expr) => format!("r{} = {}", reg, expr),
+            Stmt::Goto(target) => self.jump(*target, ctx),
+            Stmt::If(cond, target) => format!("if {} {{ {} }}", cond, self.jump(*target, ctx)),
+            Stmt::Jump(expr) => format!("goto ({}) + 1", expr),
+        }
+    }
+
+    fn push<W: Word>(&mut self, nodes: &[Node<W>], depth: usize, ctx: LoopCtx) {
+        for node in nodes {
+            match node {
+                Node::Stmt(at, stmt) => {
+                    let line = self.stmt(stmt, ctx);
+                    self.lines.push((Some(*at), depth, line));
+                }
+                Node::If(cond, then, otherwise) => {
+                    self.lines.push((None, depth, format!("if {} {{", cond)));
+                    self.push(then, depth + 1, ctx);
+                    if !otherwise.is_empty() {
+                        self.lines.push((None, depth, "} else {".to_string()));
+                        self.push(otherwise, depth + 1, ctx);
+                    }
+                    self.lines.push((None, depth, "}".to_string()));
+                }
+                Node::Loop {
+                    head,
+                    exit,
+                    body,
+                    cond,
+                } => {
+                    // `continue` in a do-while would check the condition first, so it can only
+                    // mean going back to the top in a plain loop.
+                    let inner = LoopCtx {
+                        head: if cond.is_none() { Some(*head) } else { None },
+                        exit: Some(*exit),
+                    };
+                    let opening = if cond.is_some() { "do {" } else { "loop {" };
+                    self.lines.push((None, depth, opening.to_string()));
+                    self.push(body, depth + 1, inner);
+                    match cond {
+                        Some(cond) => self.lines.push((None, depth, format!("}} while {}", cond))),
+                        None => self.lines.push((None, depth, "}".to_string())),
+                    }
+                }
+            }
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut text = String::new();
+        let mut labelled = BTreeSet::new();
+        for (at, depth, line) in &self.lines {
+            if let Some(at) = at {
+                if self.labels.contains(at) && labelled.insert(*at) {
+                    text.push_str(&format!("{}L{}:\n", "    ".repeat(*depth), at));
+                }
+            }
+            text.push_str(&format!("{}    {}\n", "    ".repeat(*depth), line));
+        }
+        text
+    }
+}
+
+/// Lists the instructions with what each one does, jumps and all.
+pub fn disassemble<W: Word>(program: &Program<W>) -> String {
+    let mut text = String::new();
+    if let Some(ip_reg) = program.ip_reg() {
+        text.push_str(&format!("#ip {}\n", ip_reg));
+    }
+    let stmts = statements(program);
+    let mut lines = Lines {
+        lines: Vec::new(),
+        labels: BTreeSet::new(),
+        len: stmts.len(),
+    };
+    for (at, (ins, stmt)) in program.instructions().iter().zip(&stmts).enumerate() {
+        let line = lines.stmt(stmt, LoopCtx::default());
+        text.push_str(&format!("{:3}: {:<20} {}\n", at, ins.to_string(), line));
+    }
+    text
+}
+
+/// Pseudocode for the program, with loops and ifs where it can find them.
+pub fn decompile<W: Word>(program: &Program<W>) -> String {
+    let stmts = statements(program);
+    let nodes = structure(&stmts, 0, stmts.len());
+    let mut lines = Lines {
+        lines: Vec::new(),
+        labels: BTreeSet::new(),
+        len: stmts.len(),
+    };
+    lines.push(&nodes, 0, LoopCtx::default());
+    lines.text()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(input: &str) -> Program<u64> {
+        Program::parse(input, 6).unwrap()
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program = program("#ip 0\nseti 5 0 1\naddi 0 1 0\naddr 1 2 3\nmulr 0 0 0\n");
+        assert_eq!(
+            "#ip 0
+  0: seti 5 0 1           r1 = 5
+  1: addi 0 1 0           goto L3
+  2: addr 1 2 3           r3 = r1 + r2
+  3: mulr 0 0 0           halt
+",
+            disassemble(&program)
+        );
+    }
+
+    #[test]
+    fn test_loop() {
+        let program = program(
+            "#ip 5
+seti 0 0 0
+addi 0 1 0
+gtri 0 9 1
+addr 1 5 5
+seti 0 0 5
+addi 0 100 2
+",
+        );
+        assert_eq!(
+            "    r0 = 0
+    do {
+        r0 = r0 + 1
+        r1 = r0 > 9
+    } while r0 <= 9
+    r2 = r0 + 100
+",
+            decompile(&program)
+        );
+    }
+
+    #[test]
+    fn test_if_else() {
+        let program = program(
+            "#ip 5
+eqri 0 1 1
+addr 1 5 5
+addi 5 2 5
+seti 7 0 2
+seti 5 0 5
+seti 8 0 2
+seti 1 0 3
+",
+        );
+        assert_eq!(
+            "    r1 = r0 == 1
+    if r0 == 1 {
+        r2 = 7
+    } else {
+        r2 = 8
+    }
+    r3 = 1
+",
+            decompile(&program)
+        );
+    }
+
+    #[test]
+    fn test_gotos_left_over() {
+        // The jump back to 1 can't be a loop, because of the jump into the middle of it.
+        let program = program(
+            "#ip 5
+seti 3 0 5
+seti 1 0 1
+seti 9 0 5
+seti 2 0 2
+addr 0 5 5
+seti 0 0 5
+",
+        );
+        assert_eq!(
+            "    goto L4
+L1:
+    r1 = 1
+    halt
+    r2 = 2
+L4:
+    goto (r0 + 4) + 1
+    goto L1
+",
+            decompile(&program)
+        );
+    }
+
+    #[test]
+    fn test_big_values() {
+        // Sums that overflow stay as sums, and jumping as far as the ip goes is a halt.
+        let overflows = program(
+            "#ip 0
+seti 18446744073709551615 0 1
+addi 0 18446744073709551615 0
+",
+        );
+        assert_eq!(
+            "    r1 = 18446744073709551615
+    goto (1 + 18446744073709551615) + 1
+",
+            decompile(&overflows)
+        );
+        let halts = program("#ip 0\nseti 18446744073709551615 0 0\n");
+        assert!(disassemble(&halts).ends_with("halt\n"));
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Finding which values of an input register make a program halt, and how soon.
+//!
+//! Programs like day 21's only look at their input by checking whether it's equal to something
+//! they worked out, and halt if it is.  So instead of trying inputs, the search runs the program
+//! once with an input that's never equal, and writes down what it was compared with each time.
+//! Each of those values halts the program if it halts right after being equal.  Once the
+//! registers at a comparison are ones it's seen there before, the program is going round in a
+//! loop and won't compare with anything new.
+
+use crate::{Fault, FaultKind, Machine, Opcode, Operand, Word};
+use std::{collections::HashSet, error, fmt};
+
+/// Why the search couldn't say which inputs halt the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HaltingError {
+    /// The machine doesn't have the register.
+    NoRegister(usize),
+    /// No instruction checks whether the register is equal to something.
+    NoComparison(usize),
+    /// The instruction at `ip` reads the register for something other than checking if it's
+    /// equal, or writes it.
+    OtherUse {
+        register: usize,
+        ip: usize,
+    },
+    /// The program halted without any input making it.
+    Halted,
+    /// It didn't start going round in a loop within this many steps.
+    TooLong(usize),
+    Fault(Fault),
+}
+
+impl fmt::Display for HaltingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HaltingError::NoRegister(register) => write!(f, "there's no r{}", register),
+            HaltingError::NoComparison(register) => {
+                write!(
+                    f,
+                    "nothing checks whether r{} is equal to something",
+                    register
+                )
+            }
+            HaltingError::OtherUse { register, ip } => write!(
+                f,
+                "instruction {} uses r{} for more than checking if it's equal",
+                ip, register
+            ),
+            HaltingError::Halted => write!(f, "the program halts whatever the input is"),
+            HaltingError::TooLong(steps) => {
+                write!(f, "the program didn't start repeating in {} steps", steps)
+            }
+            HaltingError::Fault(fault) => write!(f, "{}", fault),
+        }
+    }
+}
+
+impl error::Error for HaltingError {}
+
+/// An input that halts the program, and how many steps it takes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HaltingInput<W> {
+    pub value: W,
+    /// The steps to halt, counting every instruction a macro-instruction stands for, so it's the
+    /// same however the program is optimized.
+    pub steps: usize,
+}
+
+/// Every input which halts the program, from searching it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Halting<W> {
+    /// The instructions that check whether the input is equal to something.
+    pub comparisons: Vec<usize>,
+    /// The inputs which halt it, in the order the program gets to them.
+    pub inputs: Vec<HaltingInput<W>>,
+    /// How many times it got to a comparison before it started going round in a loop.
+    pub visits: usize,
+}
+
+impl<W: Word> Halting<W> {
+    /// The input that halts the program in the fewest steps.
+    pub fn fewest_steps(&self) -> Option<HaltingInput<W>> {
+        self.inputs.iter().min_by_key(|input| input.steps).copied()
+    }
+
+    /// The input that halts the program, but only after the most steps.
+    pub fn most_steps(&self) -> Option<HaltingInput<W>> {
+        self.inputs.iter().max_by_key(|input| input.steps).copied()
+    }
+}
+
+/// The instructions which check whether `register` is equal to something.  Anything else that
+/// uses the register is an error.
+fn comparisons<W: Word, const N: usize>(
+    machine: &Machine<W, N>,
+    register: usize,
+) -> Result<Vec<usize>, HaltingError> {
+    let mut found = Vec::new();
+    for (ip, ins) in machine.program().instructions().iter().enumerate() {
+        let (a_kind, b_kind) = ins.op.operands();
+        let a_reads = a_kind == Operand::Register && ins.a.to_index() == register;
+        let b_reads = b_kind == Operand::Register && ins.b.to_index() == register;
+        let writes = ins.c.to_index() == register;
+        if !(a_reads || b_reads || writes) {
+            continue;
+        }
+        let equal = [Opcode::eqrr, Opcode::eqri, Opcode::eqir].contains(&ins.op);
+        if !equal || (a_reads && b_reads) || writes {
+            return Err(HaltingError::OtherUse { register, ip });
+        }
+        found.push(ip);
+    }
+    if found.is_empty() {
+        return Err(HaltingError::NoComparison(register));
+    }
+    Ok(found)
+}
+
+/// What the comparison the machine is at compares the input with, or the fault running it
+/// would have if that's a register the machine doesn't have.
+fn compared<W: Word, const N: usize>(machine: &Machine<W, N>, register: usize) -> Result<W, Fault> {
+    let ins = machine.next_instruction().unwrap();
+    let mut registers = *machine.registers();
+    if let Some(ip_reg) = machine.program().ip_reg() {
+        registers[ip_reg] = W::from_index(machine.ip());
+    }
+    let read = |operand: W, kind: Operand| match kind {
+        Operand::Register => registers.get(operand.to_index()).copied().ok_or(Fault {
+            ip: machine.ip(),
+            kind: FaultKind::NoRegister(operand.to_index()),
+        }),
+        _ => Ok(operand),
+    };
+    let (a_kind, b_kind) = ins.op.operands();
+    if a_kind == Operand::Register && ins.a.to_index() == register {
+        read(ins.b, b_kind)
+    } else {
+        read(ins.a, a_kind)
+    }
+}
+
+/// How many instructions the machine's next step runs: one, or all of the ones a
+/// macro-instruction stands for.
+fn instructions_in_step<W: Word, const N: usize>(machine: &Machine<W, N>) -> usize {
+    let mut registers = *machine.registers();
+    match machine.next_macro() {
+        Some(found) if found.idiom.apply(&mut registers, machine.overflow()) == Ok(true) => {
+            found.idiom.instructions(machine.registers())
+        }
+        _ => 1,
+    }
+}
+
+/// Runs `machine` from a comparison with the input equal, and returns how many instructions it
+/// runs to halt, or None if it gets to another comparison first.
+fn steps_to_halt<W: Word, const N: usize>(
+    mut machine: Machine<W, N>,
+    comparisons: &[usize],
+) -> Result<Option<usize>, Fault> {
+    let mut steps = 0usize;
+    loop {
+        let instructions = instructions_in_step(&machine);
+        if !machine.step()? {
+            return Ok(Some(steps));
+        }
+        steps = steps.saturating_add(instructions);
+        if comparisons.contains(&machine.ip()) {
+            return Ok(None);
+        }
+    }
+}
+
+/// Searches for the values of `register` that make `machine` halt, running it for at most
+/// `limit` steps.  The register has to only be read by instructions that check whether it's
+/// equal to something, and never written; whatever it has in `machine` is ignored.
+pub fn halting_inputs<W: Word, const N: usize>(
+    mut machine: Machine<W, N>,
+    register: usize,
+    limit: usize,
+) -> Result<Halting<W>, HaltingError> {
+    if register >= N {
+        return Err(HaltingError::NoRegister(register));
+    }
+    let comparisons = comparisons(&machine, register)?;
+    let mut seen_states = HashSet::new();
+    let mut seen_values = HashSet::new();
+    let mut inputs = Vec::new();
+    let mut visits = 0;
+    // The instructions run so far, which is more than the steps if there are macro-instructions.
+    let mut steps = 0usize;
+    for _ in 0..limit {
+        if comparisons.contains(&machine.ip()) && machine.next_macro().is_none() {
+            let value = compared(&machine, register).map_err(HaltingError::Fault)?;
+            // Something that isn't the value, so the program carries on.
+            machine.registers_mut()[register] = if value == W::default() {
+                W::from(1)
+            } else {
+                W::default()
+            };
+            let mut state = *machine.registers();
+            state[register] = W::default();
+            if !seen_states.insert((machine.ip(), state)) {
+                return Ok(Halting {
+                    comparisons,
+                    inputs,
+                    visits,
+                });
+            }
+            visits += 1;
+            if seen_values.insert(value) {
+                let mut equal = machine.clone();
+                equal.registers_mut()[register] = value;
+                let halts = steps_to_halt(equal, &comparisons).map_err(HaltingError::Fault)?;
+                if let Some(more) = halts {
+                    inputs.push(HaltingInput {
+                        value,
+                        steps: steps.saturating_add(more),
+                    });
+                }
+            }
+        }
+        let instructions = instructions_in_step(&machine);
+        if !machine.step().map_err(HaltingError::Fault)? {
+            return Err(HaltingError::Halted);
+        }
+        steps = steps.saturating_add(instructions);
+    }
+    Err(HaltingError::TooLong(limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Program;
+
+    /// Counts r1 up by 3 each time round, mod 8, halting if r0 is equal to it.
+    const COUNTER: &str = "#ip 4
+seti 0 0 3
+addi 1 3 1
+bani 1 7 1
+eqrr 1 0 2
+addr 2 4 4
+seti 0 0 4
+";
+
+    #[test]
+    fn test_halting_inputs() {
+        let program = Program::parse(COUNTER, 6).unwrap();
+        let machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        let halting = halting_inputs(machine, 0, 1000).unwrap();
+        assert_eq!(vec![3], halting.comparisons);
+        assert_eq!(8, halting.visits);
+        let values: Vec<u64> = halting.inputs.iter().map(|input| input.value).collect();
+        assert_eq!(vec![3, 6, 1, 4, 7, 2, 5, 0], values);
+        assert_eq!(
+            Some(HaltingInput { value: 3, steps: 5 }),
+            halting.fewest_steps()
+        );
+        assert_eq!(
+            Some(HaltingInput {
+                value: 0,
+                steps: 40
+            }),
+            halting.most_steps()
+        );
+    }
+
+    /// Counts r4 up by 700 each time round, mod 4096, and halts if r0 is equal to r4 / 256,
+    /// which it works out a step at a time.
+    const DIVIDING: &str = "#ip 3
+seti 0 0 4
+addi 4 700 4
+bani 4 4095 4
+setr 4 0 2
+seti 0 3 1
+addi 1 1 5
+muli 5 256 5
+gtrr 5 2 5
+addr 5 3 3
+addi 3 1 3
+seti 12 9 3
+addi 1 1 1
+seti 4 4 3
+setr 1 9 2
+eqrr 2 0 5
+addr 5 3 3
+seti 0 0 3
+";
+
+    #[test]
+    fn test_halting_optimized() {
+        let program = Program::parse(DIVIDING, 6).unwrap();
+        let optimized = program.clone().optimize();
+        assert_eq!(1, optimized.macros().count());
+        let plain = halting_inputs(Machine::<u64, 6>::new(program).unwrap(), 0, 1_000_000);
+        let optimized = halting_inputs(Machine::<u64, 6>::new(optimized).unwrap(), 0, 1_000_000);
+        let (plain, optimized) = (plain.unwrap(), optimized.unwrap());
+        assert_eq!(16, plain.inputs.len());
+        assert_eq!(plain, optimized);
+    }
+
+    #[test]
+    fn test_halting_errors() {
+        let program = Program::parse(COUNTER, 6).unwrap();
+        let machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        assert_eq!(
+            Err(HaltingError::NoComparison(5)),
+            halting_inputs(machine.clone(), 5, 1000)
+        );
+        assert_eq!(
+            Err(HaltingError::OtherUse { register: 1, ip: 1 }),
+            halting_inputs(machine.clone(), 1, 1000)
+        );
+        assert_eq!(
+            Err(HaltingError::TooLong(20)),
+            halting_inputs(machine, 0, 20)
+        );
+    }
+
+    #[test]
+    fn test_missing_registers() {
+        // Neither the input nor what it's compared with is a register the machine has.
+        let program = Program::parse(COUNTER, 7).unwrap();
+        let machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        assert_eq!(
+            Err(HaltingError::NoRegister(6)),
+            halting_inputs(machine, 6, 1000)
+        );
+        let program = Program::parse(&COUNTER.replace("eqrr 1 0 2", "eqrr 6 0 2"), 7).unwrap();
+        let machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        let fault = Fault {
+            ip: 3,
+            kind: FaultKind::NoRegister(6),
+        };
+        assert_eq!(
+            Err(HaltingError::Fault(fault)),
+            halting_inputs(machine, 0, 1000)
+        );
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Working out which opcode each number is, from samples of what instructions did.
+//!
+//! Each sample narrows down the opcodes its number could be.  Once all of them are in, the solver
+//! pins down numbers that only have one opcode left, and takes that opcode away from the rest,
+//! until nothing changes.  If there's only one way to give each number an opcode of its own, that
+//! gets all of them.  Otherwise, matching numbers to opcodes finds numbers that can't all have
+//! one, or narrows down what's left open to the opcodes which still leave one for every other
+//! number.  Which order the samples come in doesn't matter.
+
+use crate::Opcode;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error, fmt,
+};
+
+/// Why the samples don't say which number is which opcode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InferError {
+    /// No opcode does what all the samples for the number did.
+    NoOpcode(u8),
+    /// The numbers can only be the opcodes between them, and there are fewer of those.
+    TooFewOpcodes(BTreeSet<u8>, BTreeSet<Opcode>),
+    /// The numbers could each be more than one opcode.
+    Ambiguous(BTreeMap<u8, BTreeSet<Opcode>>),
+}
+
+/// Lists things like "1, 2 and 3".
+fn list<T: fmt::Display>(things: impl IntoIterator<Item = T>, and: &str) -> String {
+    let things: Vec<String> = things.into_iter().map(|t| t.to_string()).collect();
+    match things.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} {} {}", rest.join(", "), and, last),
+        None => String::new(),
+    }
+}
+
+impl fmt::Display for InferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InferError::NoOpcode(number) => {
+                write!(f, "no opcode does what the samples for {} did", number)
+            }
+            InferError::TooFewOpcodes(numbers, opcodes) => write!(
+                f,
+                "{} can only be {} between them",
+                list(numbers, "and"),
+                list(opcodes, "and")
+            ),
+            InferError::Ambiguous(open) => {
+                let open = open.iter().map(|(number, opcodes)| {
+                    format!("{} could be {}", number, list(opcodes, "or"))
+                });
+                write!(
+                    f,
+                    "the samples don't pin down every opcode: {}",
+                    list(open, "and")
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for InferError {}
+
+/// Which opcodes each number could still be.
+type Possible = BTreeMap<u8, BTreeSet<Opcode>>;
+
+/// Tries to find a different opcode for `number` by moving the numbers which have the ones it
+/// could be onto others.  `seen` gets every number it tried to move.
+fn augment(
+    possible: &Possible,
+    number: u8,
+    owner: &mut BTreeMap<Opcode, u8>,
+    seen: &mut BTreeSet<u8>,
+) -> bool {
+    seen.insert(number);
+    for &opcode in &possible[&number] {
+        let free = match owner.get(&opcode) {
+            None => true,
+            Some(&other) => !seen.contains(&other) && augment(possible, other, owner, seen),
+        };
+        if free {
+            owner.insert(opcode, number);
+            return true;
+        }
+    }
+    false
+}
+
+/// Gives each number a different opcode, if it can.  If it can't, returns numbers which don't
+/// have enough opcodes between them.
+fn matching(possible: &Possible) -> Result<BTreeMap<Opcode, u8>, BTreeSet<u8>> {
+    let mut owner = BTreeMap::new();
+    for &number in possible.keys() {
+        let mut seen = BTreeSet::new();
+        if !augment(possible, number, &mut owner, &mut seen) {
+            return Err(seen);
+        }
+    }
+    Ok(owner)
+}
+
+/// Takes `opcode` for `number` and away from the other numbers.  Returns whether that changed
+/// anything.
+fn settle(possible: &mut Possible, number: u8, opcode: Opcode) -> bool {
+    let mut changed = false;
+    for (&other, opcodes) in possible.iter_mut() {
+        if other == number {
+            changed |= opcodes.len() > 1;
+            opcodes.retain(|&op| op == opcode);
+        } else {
+            changed |= opcodes.remove(&opcode);
+        }
+    }
+    changed
+}
+
+/// Settles numbers with one opcode left until there aren't any more.
+fn propagate(possible: &mut Possible) {
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let singles: Vec<(u8, Opcode)> = possible
+            .iter()
+            .filter(|(_, opcodes)| opcodes.len() == 1)
+            .map(|(&number, opcodes)| (number, *opcodes.iter().next().unwrap()))
+            .collect();
+        for (number, opcode) in singles {
+            changed |= settle(possible, number, opcode);
+        }
+    }
+}
+
+/// Collects samples, and works out which opcode each number is once they're all in.
+#[derive(Clone, Debug, Default)]
+pub struct OpcodeSolver {
+    possible: Possible,
+}
+
+impl OpcodeSolver {
+    pub fn new() -> OpcodeSolver {
+        OpcodeSolver::default()
+    }
+
+    /// Records a sample of `number`, which did what `fits` would have done.
+    pub fn add_sample(&mut self, number: u8, fits: &[Opcode]) {
+        self.possible
+            .entry(number)
+            .or_insert_with(|| Opcode::ALL.iter().cloned().collect())
+            .retain(|op| fits.contains(op));
+    }
+
+    /// The opcodes `number` could be going by its samples alone, or None if it has no samples.
+    pub fn possible(&self, number: u8) -> Option<&BTreeSet<Opcode>> {
+        self.possible.get(&number)
+    }
+
+    /// The opcode for each number that has samples, if there's only one way to have them.
+    pub fn solve(&self) -> Result<BTreeMap<u8, Opcode>, InferError> {
+        if let Some((&number, _)) = self.possible.iter().find(|(_, ops)| ops.is_empty()) {
+            return Err(InferError::NoOpcode(number));
+        }
+        if let Err(numbers) = matching(&self.possible) {
+            let opcodes = numbers
+                .iter()
+                .flat_map(|number| self.possible[number].iter().cloned())
+                .collect();
+            return Err(InferError::TooFewOpcodes(numbers, opcodes));
+        }
+        // Propagating never takes away an opcode a matching needs, so nothing ends up empty.
+        let mut possible = self.possible.clone();
+        propagate(&mut possible);
+        // Only keep the opcodes that still leave one for everyone else.
+        for (&number, opcodes) in &self.possible {
+            for &opcode in opcodes {
+                let mut forced = possible.clone();
+                if forced[&number].contains(&opcode) {
+                    settle(&mut forced, number, opcode);
+                    if matching(&forced).is_err() {
+                        possible.get_mut(&number).unwrap().remove(&opcode);
+                    }
+                }
+            }
+        }
+        let open: Possible = possible
+            .iter()
+            .filter(|(_, ops)| ops.len() > 1)
+            .map(|(&number, ops)| (number, ops.clone()))
+            .collect();
+        if !open.is_empty() {
+            return Err(InferError::Ambiguous(open));
+        }
+        Ok(possible
+            .into_iter()
+            .map(|(number, ops)| (number, *ops.iter().next().unwrap()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Opcode::*;
+
+    #[test]
+    fn test_solve() {
+        let mut solver = OpcodeSolver::new();
+        // 2 is pinned down straight away, then 0 once it's out of the way, and then 1.
+        solver.add_sample(0, &[addr, addi, mulr]);
+        solver.add_sample(0, &[addr, mulr, seti]);
+        solver.add_sample(1, &[addr, mulr, eqrr]);
+        solver.add_sample(2, &[mulr]);
+        assert_eq!(
+            Some(&[addr, mulr].iter().cloned().collect()),
+            solver.possible(0)
+        );
+        let solved = solver.solve().unwrap();
+        let expected: BTreeMap<u8, Opcode> =
+            vec![(0, addr), (1, eqrr), (2, mulr)].into_iter().collect();
+        assert_eq!(expected, solved);
+
+        // The samples coming in another order gives the same answer.
+        let mut solver = OpcodeSolver::new();
+        solver.add_sample(2, &[mulr]);
+        solver.add_sample(1, &[addr, mulr, eqrr]);
+        solver.add_sample(0, &[addr, mulr, seti]);
+        solver.add_sample(0, &[addr, addi, mulr]);
+        assert_eq!(expected, solver.solve().unwrap());
+    }
+
+    #[test]
+    fn test_matching() {
+        // Nothing is pinned down, but 2 can't be addr or mulr, because 0 and 1 need them.
+        let mut solver = OpcodeSolver::new();
+        solver.add_sample(0, &[addr, mulr]);
+        solver.add_sample(1, &[addr, mulr]);
+        solver.add_sample(2, &[addr, mulr, banr, seti]);
+        solver.add_sample(3, &[banr, seti]);
+        let open = match solver.solve() {
+            Err(InferError::Ambiguous(open)) => open,
+            other => panic!("Expected it to be ambiguous, got {:?}", other),
+        };
+        assert_eq!(
+            vec![banr, seti],
+            open[&2].iter().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(4, open.len());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut solver = OpcodeSolver::new();
+        solver.add_sample(5, &[addr, mulr]);
+        solver.add_sample(5, &[seti]);
+        assert_eq!(Err(InferError::NoOpcode(5)), solver.solve());
+        assert_eq!(
+            "no opcode does what the samples for 5 did",
+            solver.solve().unwrap_err().to_string()
+        );
+
+        let mut solver = OpcodeSolver::new();
+        solver.add_sample(0, &[addr, mulr]);
+        solver.add_sample(1, &[addr, mulr]);
+        solver.add_sample(2, &[addr, mulr, seti]);
+        solver.add_sample(3, &[addr, mulr]);
+        assert_eq!(
+            "0, 1 and 3 can only be addr and mulr between them",
+            solver.solve().unwrap_err().to_string()
+        );
+
+        let mut solver = OpcodeSolver::new();
+        solver.add_sample(0, &[addr, mulr]);
+        solver.add_sample(1, &[addr, mulr]);
+        solver.add_sample(2, &[seti]);
+        assert_eq!(
+            "the samples don't pin down every opcode: 0 could be addr or mulr and 1 could be \
+             addr or mulr",
+            solver.solve().unwrap_err().to_string()
+        );
+    }
+}
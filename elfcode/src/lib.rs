@@ -0,0 +1,411 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! The device's ElfCode: sixteen opcodes working on a handful of registers, from days 16, 19 and
+//! 21.
+//!
+//! Day 16 has four 16-bit registers and no instruction pointer, and days 19 and 21 have six bigger
+//! registers with the instruction pointer bound to one of them.  So the machine is generic over
+//! how many registers there are and what's in them.  What happens when a result doesn't fit is
+//! up to the machine too: by default it stops with a `Fault`, like it does for an instruction
+//! that names a register that isn't there.
+
+mod assemble;
+mod compile;
+mod debugger;
+mod decompile;
+mod halting;
+mod infer;
+mod machine;
+mod optimize;
+mod parse;
+mod trace;
+
+pub use crate::{
+    compile::{to_rust, Compiled},
+    debugger::{Debugger, Stop, Watch, Watchpoint},
+    decompile::{decompile, disassemble},
+    halting::{halting_inputs, Halting, HaltingError, HaltingInput},
+    infer::{InferError, OpcodeSolver},
+    machine::{Machine, Program},
+    optimize::{Idiom, Macro},
+    trace::{diff, Compare, Divergence, Recorder, TraceEntry, TraceReader},
+};
+
+use std::{
+    error, fmt,
+    hash::Hash,
+    ops::{Add, BitAnd, BitOr, Div, Mul, Rem},
+    str::FromStr,
+};
+
+/// What a register holds.
+pub trait Word:
+    Copy
+    + Default
+    + fmt::Debug
+    + fmt::Display
+    + Ord
+    + Hash
+    + FromStr
+    + From<bool>
+    + From<u8>
+    + Add<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+{
+    /// The word as a register number or instruction pointer.
+    fn to_index(self) -> usize;
+
+    /// An instruction pointer as a word.
+    fn from_index(idx: usize) -> Self;
+
+    /// Adds, and says whether it wrapped around.
+    fn overflowing_add(self, rhs: Self) -> (Self, bool);
+
+    /// Multiplies, and says whether it wrapped around.
+    fn overflowing_mul(self, rhs: Self) -> (Self, bool);
+
+    /// The biggest word there is.
+    fn max_value() -> Self;
+}
+
+macro_rules! word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                fn to_index(self) -> usize {
+                    self as usize
+                }
+
+                fn from_index(idx: usize) -> $t {
+                    idx as $t
+                }
+
+                fn overflowing_add(self, rhs: $t) -> ($t, bool) {
+                    <$t>::overflowing_add(self, rhs)
+                }
+
+                fn overflowing_mul(self, rhs: $t) -> ($t, bool) {
+                    <$t>::overflowing_mul(self, rhs)
+                }
+
+                fn max_value() -> $t {
+                    <$t>::MAX
+                }
+            }
+        )*
+    };
+}
+
+word!(u8, u16, u32, u64);
+
+/// What to do when an `add` or `mul` doesn't fit in a word.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Keep the low bits.
+    Wrapping,
+    /// Stop at the biggest word.
+    Saturating,
+    /// Stop the machine with a fault.
+    #[default]
+    Trapping,
+}
+
+impl Overflow {
+    /// Deals with the result of an overflowing add or mul.
+    fn apply<W: Word>(self, (value, overflowed): (W, bool)) -> Result<W, FaultKind> {
+        match (self, overflowed) {
+            (_, false) | (Overflow::Wrapping, true) => Ok(value),
+            (Overflow::Saturating, true) => Ok(W::max_value()),
+            (Overflow::Trapping, true) => Err(FaultKind::Overflow),
+        }
+    }
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Overflow, String> {
+        match s {
+            "wrapping" => Ok(Overflow::Wrapping),
+            "saturating" => Ok(Overflow::Saturating),
+            "trapping" => Ok(Overflow::Trapping),
+            _ => Err(format!("{} isn't wrapping, saturating or trapping", s)),
+        }
+    }
+}
+
+/// Why an instruction couldn't run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultKind {
+    /// An operand is a register that isn't there.
+    NoRegister(usize),
+    /// The result doesn't fit in a word.
+    Overflow,
+    /// The instruction pointer is bound to a register that isn't there.
+    NoIpRegister(usize),
+}
+
+/// An instruction that couldn't run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fault {
+    pub ip: usize,
+    pub kind: FaultKind,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            FaultKind::NoRegister(register) => {
+                write!(
+                    f,
+                    "instruction {} uses r{}, which isn't there",
+                    self.ip, register
+                )
+            }
+            FaultKind::Overflow => write!(f, "instruction {} overflowed", self.ip),
+            FaultKind::NoIpRegister(register) => {
+                write!(f, "the ip is bound to r{}, which isn't there", register)
+            }
+        }
+    }
+}
+
+impl error::Error for Fault {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[allow(non_camel_case_types)]
+#[rustfmt::skip]
+pub enum Opcode {
+    addr, addi, mulr, muli, banr, bani, borr, bori, setr, seti, gtir, gtri, gtrr, eqir, eqri, eqrr,
+}
+
+impl Opcode {
+    /// All of the opcodes, in the order the puzzle lists them.
+    #[rustfmt::skip]
+    pub const ALL: [Opcode; 16] = [
+        Opcode::addr, Opcode::addi, Opcode::mulr, Opcode::muli,
+        Opcode::banr, Opcode::bani, Opcode::borr, Opcode::bori,
+        Opcode::setr, Opcode::seti, Opcode::gtir, Opcode::gtri,
+        Opcode::gtrr, Opcode::eqir, Opcode::eqri, Opcode::eqrr,
+    ];
+
+    /// The opcode written the way programs spell it, like `addi`.
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+        Opcode::ALL
+            .iter()
+            .find(|op| op.to_string() == mnemonic)
+            .cloned()
+    }
+
+    /// What the opcode does with its `a` and `b` operands.  `c` is always a register.
+    pub fn operands(&self) -> (Operand, Operand) {
+        use Operand::*;
+        match self {
+            Opcode::addr | Opcode::mulr | Opcode::banr | Opcode::borr => (Register, Register),
+            Opcode::addi | Opcode::muli | Opcode::bani | Opcode::bori => (Register, Immediate),
+            Opcode::setr => (Register, Ignored),
+            Opcode::seti => (Immediate, Ignored),
+            Opcode::gtir | Opcode::eqir => (Immediate, Register),
+            Opcode::gtri | Opcode::eqri => (Register, Immediate),
+            Opcode::gtrr | Opcode::eqrr => (Register, Register),
+        }
+    }
+
+    /// Runs the opcode on `registers` with inputs `a` and `b`, putting the result in register
+    /// `c`.  Leaves the registers alone if it can't.
+    pub fn execute<W: Word, const N: usize>(
+        &self,
+        registers: &mut [W; N],
+        a: W,
+        b: W,
+        c: W,
+        overflow: Overflow,
+    ) -> Result<(), FaultKind> {
+        let reg = |x: W| {
+            registers
+                .get(x.to_index())
+                .cloned()
+                .ok_or_else(|| FaultKind::NoRegister(x.to_index()))
+        };
+        let value = match self {
+            Opcode::addr => overflow.apply(reg(a)?.overflowing_add(reg(b)?))?,
+            Opcode::addi => overflow.apply(reg(a)?.overflowing_add(b))?,
+            Opcode::mulr => overflow.apply(reg(a)?.overflowing_mul(reg(b)?))?,
+            Opcode::muli => overflow.apply(reg(a)?.overflowing_mul(b))?,
+            Opcode::banr => reg(a)? & reg(b)?,
+            Opcode::bani => reg(a)? & b,
+            Opcode::borr => reg(a)? | reg(b)?,
+            Opcode::bori => reg(a)? | b,
+            Opcode::setr => reg(a)?,
+            Opcode::seti => a,
+            Opcode::gtir => W::from(a > reg(b)?),
+            Opcode::gtri => W::from(reg(a)? > b),
+            Opcode::gtrr => W::from(reg(a)? > reg(b)?),
+            Opcode::eqir => W::from(a == reg(b)?),
+            Opcode::eqri => W::from(reg(a)? == b),
+            Opcode::eqrr => W::from(reg(a)? == reg(b)?),
+        };
+        let c = c.to_index();
+        *registers.get_mut(c).ok_or(FaultKind::NoRegister(c))? = value;
+        Ok(())
+    }
+
+    /// Runs the opcode like `execute`, wrapping around on overflow.  Panics if an operand is a
+    /// register that isn't there.
+    pub fn apply<W: Word, const N: usize>(&self, registers: &mut [W; N], a: W, b: W, c: W) {
+        if let Err(kind) = self.execute(registers, a, b, c, Overflow::Wrapping) {
+            panic!("Can't run {:?} {} {} {}: {:?}", self, a, b, c, kind);
+        }
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// How an opcode uses an operand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// The operand is the number of a register, and the value is in the register.
+    Register,
+    /// The operand is the value.
+    Immediate,
+    Ignored,
+}
+
+/// An opcode and its three operands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Instruction<W> {
+    pub op: Opcode,
+    pub a: W,
+    pub b: W,
+    pub c: W,
+}
+
+impl<W: Word> Instruction<W> {
+    pub fn new(op: Opcode, a: W, b: W, c: W) -> Instruction<W> {
+        Instruction { op, a, b, c }
+    }
+
+    pub fn execute<const N: usize>(
+        &self,
+        registers: &mut [W; N],
+        overflow: Overflow,
+    ) -> Result<(), FaultKind> {
+        self.op.execute(registers, self.a, self.b, self.c, overflow)
+    }
+
+    pub fn apply<const N: usize>(&self, registers: &mut [W; N]) {
+        self.op.apply(registers, self.a, self.b, self.c)
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for Instruction<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {} {} {}", self.op, self.a, self.b, self.c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn after(op: Opcode, a: u64, b: u64) -> u64 {
+        let mut registers: [u64; 4] = [3, 5, 0, 0];
+        op.apply(&mut registers, a, b, 3);
+        registers[3]
+    }
+
+    #[test]
+    fn test_opcodes() {
+        assert_eq!(8, after(Opcode::addr, 0, 1));
+        assert_eq!(4, after(Opcode::addi, 0, 1));
+        assert_eq!(15, after(Opcode::mulr, 0, 1));
+        assert_eq!(6, after(Opcode::muli, 0, 2));
+        assert_eq!(1, after(Opcode::banr, 0, 1));
+        assert_eq!(2, after(Opcode::bani, 0, 6));
+        assert_eq!(7, after(Opcode::borr, 0, 1));
+        assert_eq!(7, after(Opcode::bori, 0, 4));
+        assert_eq!(5, after(Opcode::setr, 1, 9));
+        assert_eq!(9, after(Opcode::seti, 9, 1));
+        assert_eq!(1, after(Opcode::gtir, 4, 0));
+        assert_eq!(0, after(Opcode::gtri, 0, 3));
+        assert_eq!(0, after(Opcode::gtrr, 0, 1));
+        assert_eq!(1, after(Opcode::eqir, 5, 1));
+        assert_eq!(1, after(Opcode::eqri, 0, 3));
+        assert_eq!(0, after(Opcode::eqrr, 0, 1));
+    }
+
+    #[test]
+    fn test_faults() {
+        let mut registers: [u16; 4] = [60000, 2, 0, 0];
+        let op = Opcode::addr;
+        assert_eq!(
+            Err(FaultKind::Overflow),
+            op.execute(&mut registers, 0, 0, 3, Overflow::Trapping)
+        );
+        assert_eq!([60000, 2, 0, 0], registers);
+        assert_eq!(
+            Ok(()),
+            op.execute(&mut registers, 0, 0, 3, Overflow::Wrapping)
+        );
+        assert_eq!(54464, registers[3]);
+        assert_eq!(
+            Ok(()),
+            Opcode::muli.execute(&mut registers, 0, 2, 3, Overflow::Saturating)
+        );
+        assert_eq!(65535, registers[3]);
+
+        assert_eq!(
+            Err(FaultKind::NoRegister(4)),
+            Opcode::setr.execute(&mut registers, 4, 0, 3, Overflow::Trapping)
+        );
+        assert_eq!(
+            Err(FaultKind::NoRegister(7)),
+            Opcode::seti.execute(&mut registers, 4, 0, 7, Overflow::Trapping)
+        );
+        // seti's a is a value, so it doesn't matter that there's no register 4.
+        assert_eq!(
+            Ok(()),
+            Opcode::seti.execute(&mut registers, 4, 0, 3, Overflow::Trapping)
+        );
+        let fault = Fault {
+            ip: 3,
+            kind: FaultKind::NoRegister(7),
+        };
+        assert_eq!(
+            "instruction 3 uses r7, which isn't there",
+            fault.to_string()
+        );
+        assert_eq!(Ok(Overflow::Saturating), "saturating".parse());
+    }
+
+    #[test]
+    fn test_mnemonics() {
+        for op in Opcode::ALL.iter() {
+            assert_eq!(Some(*op), Opcode::from_mnemonic(&op.to_string()));
+        }
+        assert_eq!(None, Opcode::from_mnemonic("divr"));
+        assert_eq!(
+            (Operand::Immediate, Operand::Register),
+            Opcode::gtir.operands()
+        );
+    }
+
+    #[test]
+    fn test_instruction() {
+        // The sample from day 16.
+        let mut registers: [u16; 4] = [3, 2, 1, 1];
+        let instruction = Instruction::new(Opcode::mulr, 2, 1, 2);
+        instruction.apply(&mut registers);
+        assert_eq!([3, 2, 2, 1], registers);
+        assert_eq!("mulr 2 1 2", instruction.to_string());
+    }
+}
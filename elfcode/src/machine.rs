@@ -0,0 +1,274 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+use crate::{Fault, FaultKind, Instruction, Macro, Overflow, Word};
+use std::collections::BTreeMap;
+
+/// A list of instructions, and the register the instruction pointer is bound to if it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program<W> {
+    ip_reg: Option<usize>,
+    instructions: Vec<Instruction<W>>,
+    /// Loops to run all at once, by where they start.  `optimize` finds them.
+    pub(crate) macros: BTreeMap<usize, Macro<W>>,
+}
+
+impl<W: Word> Program<W> {
+    pub fn new(ip_reg: Option<usize>, instructions: Vec<Instruction<W>>) -> Program<W> {
+        Program {
+            ip_reg,
+            instructions,
+            macros: BTreeMap::new(),
+        }
+    }
+
+    /// The register the instruction pointer is bound to.
+    pub fn ip_reg(&self) -> Option<usize> {
+        self.ip_reg
+    }
+
+    pub fn instructions(&self) -> &[Instruction<W>] {
+        &self.instructions
+    }
+
+    /// The macro-instructions, in order of where they start.
+    pub fn macros(&self) -> impl Iterator<Item = &Macro<W>> {
+        self.macros.values()
+    }
+}
+
+/// A device running a program, with `N` registers.
+#[derive(Clone, Debug)]
+pub struct Machine<W, const N: usize> {
+    program: Program<W>,
+    registers: [W; N],
+    ip: usize,
+    overflow: Overflow,
+}
+
+impl<W: Word, const N: usize> Machine<W, N> {
+    /// A machine about to run `program`, with all the registers at zero.  It stops with a fault
+    /// if an `add` or `mul` overflows.  It can't run a program whose instruction pointer is bound
+    /// to a register it doesn't have.
+    pub fn new(program: Program<W>) -> Result<Machine<W, N>, Fault> {
+        if let Some(ip_reg) = program.ip_reg.filter(|&ip_reg| ip_reg >= N) {
+            return Err(Fault {
+                ip: 0,
+                kind: FaultKind::NoIpRegister(ip_reg),
+            });
+        }
+        Ok(Machine {
+            program,
+            registers: [W::default(); N],
+            ip: 0,
+            overflow: Overflow::default(),
+        })
+    }
+
+    /// Does `overflow` when an `add` or `mul` doesn't fit.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Machine<W, N> {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn program(&self) -> &Program<W> {
+        &self.program
+    }
+
+    pub fn registers(&self) -> &[W; N] {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut [W; N] {
+        &mut self.registers
+    }
+
+    /// The instruction pointer, which is the next instruction to run.
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// Jumps to instruction `ip`.
+    pub fn set_ip(&mut self, ip: usize) {
+        self.ip = ip;
+    }
+
+    /// The next instruction to run, or None if the program has halted.
+    pub fn next_instruction(&self) -> Option<&Instruction<W>> {
+        self.program.instructions.get(self.ip)
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.next_instruction().is_none()
+    }
+
+    /// The macro-instruction that runs instead of the next instruction, if there is one.
+    pub fn next_macro(&self) -> Option<&Macro<W>> {
+        self.program.macros.get(&self.ip)
+    }
+
+    /// Runs one instruction.  If the instruction pointer is bound to a register, the register
+    /// gets the pointer before the instruction runs, and the pointer gets the register after;
+    /// a register too big to point anywhere once it's moved on halts the program.
+    /// A macro-instruction runs its whole loop in one step.  If something in the loop overflows,
+    /// trapping faults at the head of the loop, and otherwise the loop runs an instruction at a
+    /// time.  Returns false without doing
+    /// anything if the program has halted.  If the instruction can't run, the machine stays at
+    /// it and returns the fault.
+    pub fn step(&mut self) -> Result<bool, Fault> {
+        let instruction = match self.next_instruction() {
+            Some(instruction) => *instruction,
+            None => return Ok(false),
+        };
+        if let Some(&found) = self.next_macro() {
+            let ip = self.ip;
+            let mut registers = self.registers;
+            let applied = found
+                .idiom
+                .apply(&mut registers, self.overflow)
+                .map_err(|kind| Fault { ip, kind })?;
+            if applied {
+                self.registers = registers;
+                self.ip = found.exit;
+                if let Some(ip_reg) = self.program.ip_reg {
+                    self.registers[ip_reg] = W::from_index(found.exit - 1);
+                }
+                return Ok(true);
+            }
+        }
+        let mut registers = self.registers;
+        if let Some(ip_reg) = self.program.ip_reg {
+            registers[ip_reg] = W::from_index(self.ip);
+        }
+        let ip = self.ip;
+        instruction
+            .execute(&mut registers, self.overflow)
+            .map_err(|kind| Fault { ip, kind })?;
+        self.registers = registers;
+        let next = match self.program.ip_reg {
+            Some(ip_reg) => self.registers[ip_reg].to_index().checked_add(1),
+            None => self.ip.checked_add(1),
+        };
+        self.ip = next.unwrap_or(self.program.instructions.len());
+        Ok(true)
+    }
+
+    /// Runs the program until it halts, and returns how many instructions that took.
+    pub fn run(&mut self) -> Result<usize, Fault> {
+        let mut steps = 0;
+        while self.step()? {
+            steps += 1;
+        }
+        Ok(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FaultKind, Opcode};
+
+    #[test]
+    fn test_run_with_ip_bound() {
+        // The example from day 19.
+        let program = Program::new(
+            Some(0),
+            vec![
+                Instruction::new(Opcode::seti, 5, 0, 1),
+                Instruction::new(Opcode::seti, 6, 0, 2),
+                Instruction::new(Opcode::addi, 0, 1, 0),
+                Instruction::new(Opcode::addr, 1, 2, 3),
+                Instruction::new(Opcode::setr, 1, 0, 0),
+                Instruction::new(Opcode::seti, 8, 0, 4),
+                Instruction::new(Opcode::seti, 9, 0, 5),
+            ],
+        );
+        let mut machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        assert_eq!(Ok(true), machine.step());
+        assert_eq!(
+            (1, [0, 5, 0, 0, 0, 0]),
+            (machine.ip(), *machine.registers())
+        );
+        assert_eq!(Ok(4), machine.run());
+        assert!(machine.is_halted());
+        assert_eq!([6, 5, 6, 0, 0, 9], *machine.registers());
+        assert_eq!(Ok(false), machine.step());
+    }
+
+    #[test]
+    fn test_ip_out_of_range() {
+        // Pointing the ip as far as it goes leaves the program.
+        let program = Program::parse("#ip 0\nseti 18446744073709551615 0 0", 6).unwrap();
+        let mut machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        assert_eq!(Ok(1), machine.run());
+        assert!(machine.is_halted());
+
+        let program: Program<u64> = Program::new(Some(6), Vec::new());
+        let fault = Fault {
+            ip: 0,
+            kind: FaultKind::NoIpRegister(6),
+        };
+        assert_eq!(fault, Machine::<u64, 6>::new(program).unwrap_err());
+        assert_eq!(
+            "the ip is bound to r6, which isn't there",
+            fault.to_string()
+        );
+    }
+
+    #[test]
+    fn test_run_without_ip() {
+        let program = Program::new(
+            None,
+            vec![
+                Instruction::new(Opcode::seti, 7, 0, 0),
+                Instruction::new(Opcode::muli, 0, 3, 1),
+            ],
+        );
+        let mut machine: Machine<u16, 4> = Machine::new(program).unwrap();
+        machine.registers_mut()[3] = 2;
+        assert_eq!(Ok(2), machine.run());
+        assert_eq!([7, 21, 0, 2], *machine.registers());
+    }
+
+    /// Multiplies 200 by 2, and then uses a register that isn't there.
+    fn faulty<W: Word>() -> Program<W> {
+        Program::new(
+            None,
+            vec![
+                Instruction::new(Opcode::seti, W::from(200), W::from(0), W::from(0)),
+                Instruction::new(Opcode::muli, W::from(0), W::from(2), W::from(1)),
+                Instruction::new(Opcode::addr, W::from(0), W::from(6), W::from(2)),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_faults() {
+        let mut machine: Machine<u8, 4> = Machine::new(faulty()).unwrap();
+        let fault = Fault {
+            ip: 1,
+            kind: FaultKind::Overflow,
+        };
+        assert_eq!(Err(fault), machine.run());
+        assert_eq!((1, [200, 0, 0, 0]), (machine.ip(), *machine.registers()));
+
+        let mut machine: Machine<u8, 4> = Machine::new(faulty())
+            .unwrap()
+            .with_overflow(Overflow::Wrapping);
+        let fault = Fault {
+            ip: 2,
+            kind: FaultKind::NoRegister(6),
+        };
+        assert_eq!(Err(fault), machine.run());
+        assert_eq!([200, 144, 0, 0], *machine.registers());
+
+        let mut machine: Machine<u16, 4> = Machine::new(faulty()).unwrap();
+        assert_eq!(Ok(true), machine.step());
+        assert_eq!(Ok(true), machine.step());
+        assert_eq!(400, machine.registers()[1]);
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Tools for looking at ElfCode programs:
+//!
+//!     elfcode disassemble day19/input.txt
+//!     elfcode decompile day21/input.txt
+//!     elfcode optimize day19/input.txt
+//!     elfcode compile day21/input.txt > day21.rs
+//!     elfcode debug day21/input.txt
+//!     elfcode halting day21/input.txt
+//!     elfcode assemble program.elf > program.txt
+//!     elfcode trace --r0 1 day19/input.txt plain.trace
+//!     elfcode trace --r0 1 --optimize day19/input.txt optimized.trace
+//!     elfcode diff plain.trace optimized.trace
+//!
+//! `diff --flow` only compares where the programs went, for runs that started with different
+//! registers.  `halting` finds the values of r0 (or `--register N`) which halt the program.
+
+use aoc_common::ParseError;
+use elfcode::{
+    halting_inputs, Compare, Debugger, Machine, Overflow, Program, Recorder, TraceReader,
+};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    process,
+};
+
+const USAGE: &str = "Usage: elfcode disassemble|decompile|optimize|compile|debug PATH
+       elfcode halting [--register N] PATH
+       elfcode assemble PATH
+       elfcode trace [--optimize] [--r0 N] PATH OUT
+       elfcode diff [--flow] TRACE TRACE";
+
+/// Takes `flag` out of `args`, and returns whether it was there.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != before
+}
+
+/// Takes `flag` and the number after it out of `args`.
+fn take_number(args: &mut Vec<String>, flag: &str) -> Option<u64> {
+    let at = args.iter().position(|arg| arg == flag)?;
+    args.remove(at);
+    if at == args.len() {
+        eprintln!("{} needs a number\n{}", flag, USAGE);
+        process::exit(2);
+    }
+    let value = args.remove(at);
+    Some(value.parse().unwrap_or_else(|_| {
+        eprintln!("{} needs a number, not {}", flag, value);
+        process::exit(2);
+    }))
+}
+
+/// Reads the program at `path` with `parse`, which is `Program::parse` or `Program::assemble`.
+fn read(path: &str, parse: fn(&str, usize) -> Result<Program<u64>, ParseError>) -> Program<u64> {
+    let input = fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Can't read {}: {}", path, e);
+        process::exit(1);
+    });
+    parse(&input, 6).unwrap_or_else(|e| {
+        eprintln!("{}", e.in_file(path));
+        process::exit(1);
+    })
+}
+
+fn load(path: &str) -> Program<u64> {
+    read(path, Program::parse)
+}
+
+/// A machine to run `program` on, or the reason it can't.
+fn machine(program: Program<u64>) -> Machine<u64, 6> {
+    Machine::new(program).unwrap_or_else(|fault| {
+        eprintln!("{}", fault);
+        process::exit(1);
+    })
+}
+
+/// Runs the program from `r0`, recording every step to `out`.
+fn trace(program: Program<u64>, r0: u64, out: &str) -> io::Result<()> {
+    let mut machine = machine(program);
+    machine.registers_mut()[0] = r0;
+    let mut recorder = Recorder::new(BufWriter::new(File::create(out)?), &machine)?;
+    let steps = recorder.run(&mut machine)?;
+    recorder.finish()?;
+    println!("{} steps, ending with {:?}", steps, machine.registers());
+    Ok(())
+}
+
+/// Compares two traces, and returns whether they're the same.
+fn diff(left: &str, right: &str, compare: Compare) -> io::Result<bool> {
+    let left = TraceReader::new(BufReader::new(File::open(left)?))?;
+    let right = TraceReader::new(BufReader::new(File::open(right)?))?;
+    match elfcode::diff(left, right, compare)? {
+        Some(divergence) => {
+            println!("{}", divergence);
+            Ok(false)
+        }
+        None => {
+            println!("The traces are the same");
+            Ok(true)
+        }
+    }
+}
+
+/// Lists the values of `register` which halt the program, with the soonest and latest.
+fn halting(program: Program<u64>, register: usize) -> bool {
+    let machine = machine(program.optimize());
+    let halting = match halting_inputs(machine, register, 100_000_000) {
+        Ok(halting) => halting,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    println!(
+        "r{} is compared at {:?}, {} times before it repeats",
+        register, halting.comparisons, halting.visits
+    );
+    for input in &halting.inputs {
+        println!("{} halts after {} steps", input.value, input.steps);
+    }
+    if let (Some(fewest), Some(most)) = (halting.fewest_steps(), halting.most_steps()) {
+        println!("Fewest steps: {} ({} steps)", fewest.value, fewest.steps);
+        println!("Most steps: {} ({} steps)", most.value, most.steps);
+    }
+    true
+}
+
+/// Reads debugger commands from stdin until `quit` or the end of the input.
+fn debug(program: Program<u64>) -> io::Result<()> {
+    let mut debugger: Debugger<u64, 6> = Debugger::new(machine(program));
+    println!("{}", debugger.registers());
+    let stdin = io::stdin();
+    loop {
+        print!("(elfcode) ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+        match line.trim() {
+            "q" | "quit" => return Ok(()),
+            line => println!("{}", debugger.command(line)),
+        }
+    }
+}
+
+/// The commands that only need a program.
+fn run_command(command: &str, program: Program<u64>) {
+    match command {
+        "disassemble" => print!("{}", elfcode::disassemble(&program)),
+        "decompile" => print!("{}", elfcode::decompile(&program)),
+        "optimize" => {
+            for found in program.optimize().macros() {
+                println!("{}", found);
+            }
+        }
+        "compile" => print!(
+            "{}",
+            elfcode::to_rust::<u64, 6>(&program, Overflow::default())
+        ),
+        "debug" => {
+            if let Err(e) = debug(program) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        _ => {
+            eprintln!("Unknown command {}\n{}", command, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let optimize = take_flag(&mut args, "--optimize");
+    let compare = if take_flag(&mut args, "--flow") {
+        Compare::Flow
+    } else {
+        Compare::Everything
+    };
+    let r0 = take_number(&mut args, "--r0").unwrap_or(0);
+    let register = take_number(&mut args, "--register").unwrap_or(0) as usize;
+    let result = match args.as_slice() {
+        [command, left, right] if command == "diff" => diff(left, right, compare),
+        [command, path, out] if command == "trace" => {
+            let program = load(path);
+            let program = if optimize {
+                program.optimize()
+            } else {
+                program
+            };
+            trace(program, r0, out).map(|_| true)
+        }
+        [command, path] if command == "halting" => Ok(halting(load(path), register)),
+        [command, path] if command == "assemble" => {
+            print!("{}", read(path, Program::assemble));
+            Ok(true)
+        }
+        [command, path] if command != "diff" && command != "trace" => {
+            run_command(command, load(path));
+            Ok(true)
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,561 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Finding the slow loops in a program and working out what they do all at once.
+//!
+//! Days 19 and 21 are slow because they count their way to an answer a step at a time.  The
+//! optimizer looks for loops shaped like the ones they use and puts a macro-instruction at the
+//! head of each.  When the machine gets to the head it runs the macro, which leaves the registers
+//! the way the loop would have and jumps to where the loop would have left.  The instructions
+//! themselves stay as they were, so jumping into the middle of a loop still works, and the
+//! program disassembles the same.
+
+use crate::{FaultKind, Instruction, Opcode, Operand, Overflow, Program, Word};
+use std::fmt;
+
+/// What a loop works out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Idiom<W> {
+    /// Divides `n` by `d` by counting `q` up until `(q + 1) * d` is more than `n`, using
+    /// `scratch` for the product and the comparison:
+    ///
+    /// ```text
+    /// loop {
+    ///     scratch = (q + 1) * d > n
+    ///     if scratch != 0 { break }
+    ///     q = q + 1
+    /// }
+    /// ```
+    Divide {
+        q: usize,
+        d: W,
+        n: usize,
+        scratch: usize,
+    },
+    /// Adds up the divisors of `n` into `sum`, by trying every `outer * inner` there is:
+    ///
+    /// ```text
+    /// do {
+    ///     inner = 1
+    ///     do {
+    ///         scratch = outer * inner == n
+    ///         if scratch != 0 { sum = outer + sum }
+    ///         inner = inner + 1
+    ///         scratch = inner > n
+    ///     } while scratch == 0
+    ///     outer = outer + 1
+    ///     scratch = outer > n
+    /// } while scratch == 0
+    /// ```
+    DivisorSum {
+        sum: usize,
+        n: usize,
+        outer: usize,
+        inner: usize,
+        scratch: usize,
+    },
+}
+
+impl<W: Word> Idiom<W> {
+    /// The registers the loop reads, not counting the ip.  It reads every one it uses.
+    pub fn reads(&self) -> Vec<usize> {
+        match *self {
+            Idiom::Divide { q, n, scratch, .. } => vec![q, n, scratch],
+            Idiom::DivisorSum {
+                sum,
+                n,
+                outer,
+                inner,
+                scratch,
+            } => vec![sum, n, outer, inner, scratch],
+        }
+    }
+
+    /// The registers the loop writes, not counting the ip: all but `n`.
+    pub fn writes(&self) -> Vec<usize> {
+        match *self {
+            Idiom::Divide { q, scratch, .. } => vec![q, scratch],
+            Idiom::DivisorSum {
+                sum,
+                outer,
+                inner,
+                scratch,
+                ..
+            } => vec![sum, outer, inner, scratch],
+        }
+    }
+
+    /// Leaves the registers the way the loop would have, and returns true.  A register that
+    /// isn't there is a fault, like it is for an instruction.
+    ///
+    /// If something in the loop would overflow, what happens is up to `overflow`.  Trapping is
+    /// a fault straight away, rather than after running the loop up to the instruction that
+    /// overflows.  Wrapping or saturating, the loop doesn't work out what the idiom says, so this
+    /// returns false and leaves the registers alone for the loop to run an instruction at a
+    /// time.
+    pub fn apply<const N: usize>(
+        &self,
+        registers: &mut [W; N],
+        overflow: Overflow,
+    ) -> Result<bool, FaultKind> {
+        let overflowed = || match overflow {
+            Overflow::Trapping => Err(FaultKind::Overflow),
+            Overflow::Wrapping | Overflow::Saturating => Ok(false),
+        };
+        if let Some(&missing) = self.reads().iter().find(|&&r| r >= N) {
+            return Err(FaultKind::NoRegister(missing));
+        }
+        let one = W::from(1u8);
+        match *self {
+            Idiom::Divide { q, d, n, scratch } => {
+                // The last time round works out (q + 1) * d, for the q it leaves with.
+                let quotient = registers[q].max(registers[n] / d);
+                let (next, next_overflowed) = quotient.overflowing_add(one);
+                if next_overflowed || next.overflowing_mul(d).1 {
+                    return overflowed();
+                }
+                registers[q] = quotient;
+                registers[scratch] = one;
+            }
+            Idiom::DivisorSum {
+                sum,
+                n,
+                outer,
+                inner,
+                scratch,
+            } => {
+                // Every number from outer up gets tried, and from 1 up for inner, and each loop
+                // goes round at least once.  The biggest numbers it works out are the last ones
+                // for each, and the sum at the end.
+                let n_value = registers[n];
+                let from = registers[outer];
+                let (last_outer, last_inner) = (from.max(n_value), one.max(n_value));
+                let total = divisor_sum(n_value, from)
+                    .map(|divisors| registers[sum].overflowing_add(divisors))
+                    .filter(|&(_, overflowed)| !overflowed);
+                let (after_outer, outer_overflowed) = last_outer.overflowing_add(one);
+                let (after_inner, inner_overflowed) = last_inner.overflowing_add(one);
+                let products_overflow = last_outer.overflowing_mul(last_inner).1;
+                let total = match total {
+                    Some((total, _))
+                        if !outer_overflowed && !inner_overflowed && !products_overflow =>
+                    {
+                        total
+                    }
+                    _ => return overflowed(),
+                };
+                registers[sum] = total;
+                registers[outer] = after_outer;
+                registers[inner] = after_inner;
+                registers[scratch] = one;
+            }
+        }
+        Ok(true)
+    }
+
+    /// How many instructions the loop would have run from `registers` to leave the way `apply`
+    /// does, so a macro-instruction can be counted as what it stands for.  It only makes sense
+    /// for registers `apply` works on.
+    pub fn instructions<const N: usize>(&self, registers: &[W; N]) -> usize {
+        match *self {
+            Idiom::Divide { q, d, n, .. } => {
+                // Seven instructions for each time q goes up, and five to find it can't.
+                let rounds = (registers[n] / d)
+                    .to_index()
+                    .saturating_sub(registers[q].to_index());
+                rounds.saturating_mul(7).saturating_add(5)
+            }
+            Idiom::DivisorSum { n, outer, .. } => {
+                // The inner loop is eight instructions a time round, for inner from 1 up to n,
+                // and seven the last time.  The outer loop adds four, and three the last time.
+                let (n, from) = (registers[n].to_index(), registers[outer].to_index());
+                let outers = from.max(n) - from + 1;
+                let inners = n.max(1);
+                outers
+                    .saturating_mul(inners.saturating_mul(8).saturating_add(4))
+                    .saturating_sub(1)
+            }
+        }
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for Idiom<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Idiom::Divide { q, d, n, .. } => write!(f, "r{} = max(r{}, r{} / {})", q, q, n, d),
+            Idiom::DivisorSum { sum, n, outer, .. } => write!(
+                f,
+                "r{} = r{} + (sum of divisors of r{} from r{} up)",
+                sum, sum, n, outer
+            ),
+        }
+    }
+}
+
+/// A loop the machine runs in one go.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Macro<W> {
+    /// The head of the loop, where the macro runs instead.
+    pub start: usize,
+    /// The instruction the loop leaves to.
+    pub exit: usize,
+    pub idiom: Idiom<W>,
+}
+
+impl<W: fmt::Display> fmt::Display for Macro<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}, goto {}", self.start, self.idiom, self.exit)
+    }
+}
+
+/// The divisors of `n` that are at least `from`, added up, or None if that doesn't fit in a
+/// word.  Numbers divide 0 with nothing left over, but outer * inner == 0 only when outer is 0,
+/// so 0 has no divisors worth adding.
+fn divisor_sum<W: Word>(n: W, from: W) -> Option<W> {
+    let zero = W::default();
+    let one = W::from(1u8);
+    let add = |sum: W, divisor: W| match sum.overflowing_add(divisor) {
+        (sum, false) => Some(sum),
+        (_, true) => None,
+    };
+    let mut sum = zero;
+    let mut i = one;
+    // i * i <= n, without working out i * i.
+    while i <= n / i {
+        if n % i == zero {
+            let pair = n / i;
+            if i >= from {
+                sum = add(sum, i)?;
+            }
+            if pair != i && pair >= from {
+                sum = add(sum, pair)?;
+            }
+        }
+        i = i + one;
+    }
+    Some(sum)
+}
+
+/// Whether `ins` is the same instruction as `expected`, not counting operands that aren't used.
+fn same<W: Word>(ins: &Instruction<W>, expected: Instruction<W>) -> bool {
+    ins.op == expected.op
+        && ins.a == expected.a
+        && (ins.op.operands().1 == Operand::Ignored || ins.b == expected.b)
+        && ins.c == expected.c
+}
+
+/// Where `ins` goes to, if it's a `seti` to the ip.
+fn goto<W: Word>(ins: &Instruction<W>, ip_reg: usize) -> Option<usize> {
+    if ins.op == Opcode::seti && ins.c.to_index() == ip_reg {
+        ins.a.to_index().checked_add(1)
+    } else {
+        None
+    }
+}
+
+/// Whether `ins` is `op x y c` or `op y x c`, for an opcode where the order doesn't matter.
+fn commutes<W: Word>(ins: &Instruction<W>, op: Opcode, x: W, y: W, c: W) -> bool {
+    same(ins, Instruction::new(op, x, y, c)) || same(ins, Instruction::new(op, y, x, c))
+}
+
+/// Whether `ins` adds `cond` to the ip, skipping the next instruction if it's 1.
+fn skips_if<W: Word>(ins: &Instruction<W>, cond: W, ip: W) -> bool {
+    commutes(ins, Opcode::addr, cond, ip, ip)
+}
+
+/// Whether all the registers are different ones.
+fn distinct(registers: &[usize]) -> bool {
+    registers
+        .iter()
+        .enumerate()
+        .all(|(i, r)| !registers[i + 1..].contains(r))
+}
+
+/// The division loop starting at `head`, and the instruction it leaves to.
+fn divide<W: Word>(code: &[Instruction<W>], ip_reg: usize, head: usize) -> Option<Macro<W>> {
+    let ins = code.get(head..head + 8)?;
+    let ip = W::from_index(ip_reg);
+    let one = W::from(1u8);
+    let (q, scratch, d, n) = (ins[0].a, ins[0].c, ins[1].b, ins[2].b);
+    let exit = goto(&ins[5], ip_reg)?;
+    let shape = same(&ins[0], Instruction::new(Opcode::addi, q, one, scratch))
+        && same(&ins[1], Instruction::new(Opcode::muli, scratch, d, scratch))
+        && same(&ins[2], Instruction::new(Opcode::gtrr, scratch, n, scratch))
+        && skips_if(&ins[3], scratch, ip)
+        && same(&ins[4], Instruction::new(Opcode::addi, ip, one, ip))
+        && same(&ins[6], Instruction::new(Opcode::addi, q, one, q))
+        && goto(&ins[7], ip_reg) == Some(head);
+    let (q, scratch, n) = (q.to_index(), scratch.to_index(), n.to_index());
+    if !shape || !distinct(&[q, scratch, n, ip_reg]) || d == W::default() {
+        return None;
+    }
+    Some(Macro {
+        start: head,
+        exit,
+        idiom: Idiom::Divide { q, d, n, scratch },
+    })
+}
+
+/// The nested divisor loops whose outer loop starts at `head`.
+fn divisor_sum_loop<W: Word>(
+    code: &[Instruction<W>],
+    ip_reg: usize,
+    head: usize,
+) -> Option<Macro<W>> {
+    let ins = code.get(head..head + 14)?;
+    let ip = W::from_index(ip_reg);
+    let one = W::from(1u8);
+    let inner = ins[0].c;
+    let outer = if ins[1].a == inner {
+        ins[1].b
+    } else {
+        ins[1].a
+    };
+    let (scratch, n, sum) = (ins[1].c, ins[2].b, ins[5].c);
+    let shape = same(&ins[0], Instruction::new(Opcode::seti, one, one, inner))
+        && commutes(&ins[1], Opcode::mulr, outer, inner, scratch)
+        && same(&ins[2], Instruction::new(Opcode::eqrr, scratch, n, scratch))
+        && skips_if(&ins[3], scratch, ip)
+        && same(&ins[4], Instruction::new(Opcode::addi, ip, one, ip))
+        && commutes(&ins[5], Opcode::addr, outer, sum, sum)
+        && same(&ins[6], Instruction::new(Opcode::addi, inner, one, inner))
+        && same(&ins[7], Instruction::new(Opcode::gtrr, inner, n, scratch))
+        && skips_if(&ins[8], scratch, ip)
+        && goto(&ins[9], ip_reg) == Some(head + 1)
+        && same(&ins[10], Instruction::new(Opcode::addi, outer, one, outer))
+        && same(&ins[11], Instruction::new(Opcode::gtrr, outer, n, scratch))
+        && skips_if(&ins[12], scratch, ip)
+        && goto(&ins[13], ip_reg) == Some(head);
+    let [sum, n, outer, inner, scratch] = [sum, n, outer, inner, scratch].map(W::to_index);
+    if !shape || !distinct(&[sum, n, outer, inner, scratch, ip_reg]) {
+        return None;
+    }
+    Some(Macro {
+        start: head,
+        exit: head + 14,
+        idiom: Idiom::DivisorSum {
+            sum,
+            n,
+            outer,
+            inner,
+            scratch,
+        },
+    })
+}
+
+impl<W: Word> Program<W> {
+    /// Puts a macro-instruction at the head of every loop it knows how to run all at once.  The
+    /// loops jump with the instruction pointer, so a program without it bound is left alone.
+    pub fn optimize(mut self) -> Program<W> {
+        let ip_reg = match self.ip_reg() {
+            Some(ip_reg) => ip_reg,
+            None => return self,
+        };
+        let code = self.instructions();
+        let found: Vec<Macro<W>> = (0..code.len())
+            .filter_map(|head| {
+                divisor_sum_loop(code, ip_reg, head).or_else(|| divide(code, ip_reg, head))
+            })
+            .collect();
+        for found in found {
+            self.macros.insert(found.start, found);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fault, Machine, Overflow};
+
+    /// Runs `program` from `ip` with `registers`, with and without the optimizer, and checks they
+    /// end up the same, counting the same instructions.  Returns the registers, and how many
+    /// steps the optimized one took.
+    fn check_same(program: &str, ip: usize, registers: [u64; 6]) -> ([u64; 6], usize) {
+        let program: Program<u64> = Program::parse(program, 6).unwrap();
+        let mut plain = Machine::new(program.clone()).unwrap();
+        let mut optimized = Machine::new(program.optimize()).unwrap();
+        for machine in [&mut plain, &mut optimized] {
+            *machine.registers_mut() = registers;
+            machine.set_ip(ip);
+        }
+        let instructions = plain.run().unwrap();
+        let (mut steps, mut counted) = (0, 0);
+        loop {
+            let mut registers = *optimized.registers();
+            let ran = match optimized.next_macro() {
+                Some(found)
+                    if found.idiom.apply(&mut registers, Overflow::Trapping) == Ok(true) =>
+                {
+                    found.idiom.instructions(optimized.registers())
+                }
+                _ => 1,
+            };
+            if !optimized.step().unwrap() {
+                break;
+            }
+            steps += 1;
+            counted += ran;
+        }
+        assert_eq!(instructions, counted);
+        assert_eq!(plain.registers(), optimized.registers());
+        assert_eq!(plain.ip(), optimized.ip());
+        (*optimized.registers(), steps)
+    }
+
+    // The division from day 21, jumping out to 9.
+    const DIVIDE: &str = "#ip 3
+seti 0 3 1
+addi 1 1 5
+muli 5 256 5
+gtrr 5 2 5
+addr 5 3 3
+addi 3 1 3
+seti 8 9 3
+addi 1 1 1
+seti 0 4 3
+setr 1 9 2
+";
+
+    #[test]
+    fn test_divide() {
+        let program: Program<u64> = Program::parse(DIVIDE, 6).unwrap().optimize();
+        let macros: Vec<String> = program.macros().map(|m| m.to_string()).collect();
+        assert_eq!(vec!["1: r1 = max(r1, r2 / 256), goto 9"], macros);
+
+        assert_eq!(
+            ([0, 3, 3, 9, 0, 1], 3),
+            check_same(DIVIDE, 0, [0, 0, 1000, 0, 0, 0])
+        );
+        assert_eq!(
+            ([0, 0, 0, 9, 0, 1], 3),
+            check_same(DIVIDE, 0, [0, 0, 255, 0, 0, 0])
+        );
+        // Starting past the answer, it goes round once.
+        check_same(DIVIDE, 1, [0, 7, 1000, 0, 0, 0]);
+        // Jumping into the middle of the loop runs it the slow way.
+        check_same(DIVIDE, 7, [0, 0, 1000, 0, 0, 0]);
+    }
+
+    // The divisor sum from day 19, without the setup.
+    const DIVISOR_SUM: &str = "#ip 4
+addi 4 16 4
+seti 1 2 5
+seti 1 1 1
+mulr 5 1 2
+eqrr 2 3 2
+addr 2 4 4
+addi 4 1 4
+addr 5 0 0
+addi 1 1 1
+gtrr 1 3 2
+addr 4 2 4
+seti 2 4 4
+addi 5 1 5
+gtrr 5 3 2
+addr 2 4 4
+seti 1 8 4
+mulr 4 4 4
+";
+
+    #[test]
+    fn test_divisor_sum() {
+        let program: Program<u64> = Program::parse(DIVISOR_SUM, 6).unwrap().optimize();
+        let macros: Vec<String> = program.macros().map(|m| m.to_string()).collect();
+        assert_eq!(
+            vec!["2: r0 = r0 + (sum of divisors of r3 from r5 up), goto 16"],
+            macros
+        );
+
+        for (n, sum) in [(1, 1), (7, 8), (36, 91), (100, 217)] {
+            let (registers, steps) = check_same(DIVISOR_SUM, 1, [0, 0, 0, n, 0, 0]);
+            assert_eq!((sum, 3), (registers[0], steps));
+        }
+        // Starting part way through the outer loop, or with a sum already going.
+        check_same(DIVISOR_SUM, 2, [5, 0, 0, 36, 0, 4]);
+        check_same(DIVISOR_SUM, 2, [0, 0, 0, 10, 0, 20]);
+        check_same(DIVISOR_SUM, 1, [0, 0, 0, 0, 0, 0]);
+
+        // Multiplying the other way round.
+        let swapped = DIVISOR_SUM.replace("mulr 5 1 2", "mulr 1 5 2");
+        assert_eq!(
+            ([91, 37, 1, 36, 256, 37], 3),
+            check_same(&swapped, 1, [0, 0, 0, 36, 0, 0])
+        );
+    }
+
+    /// Runs `program` on 8-bit registers from `ip` with `registers`, with and without the
+    /// optimizer, and checks they end up the same, or fault the same way.  The macro traps at
+    /// the head of the loop, so not at the same place.
+    fn check_same_u8(program: &str, ip: usize, registers: [u8; 6], overflow: Overflow) {
+        let program: Program<u8> = Program::parse(program, 6).unwrap();
+        let mut plain = Machine::new(program.clone())
+            .unwrap()
+            .with_overflow(overflow);
+        let mut optimized = Machine::new(program.optimize())
+            .unwrap()
+            .with_overflow(overflow);
+        assert_eq!(1, optimized.program().macros().count());
+        for machine in [&mut plain, &mut optimized] {
+            *machine.registers_mut() = registers;
+            machine.set_ip(ip);
+        }
+        match (plain.run(), optimized.run()) {
+            (Err(plain), Err(optimized)) => assert_eq!(plain.kind, optimized.kind),
+            (plain_ended, optimized_ended) => {
+                assert_eq!(plain_ended.map(|_| ()), optimized_ended.map(|_| ()));
+                assert_eq!(plain.registers(), optimized.registers());
+                assert_eq!(plain.ip(), optimized.ip());
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflowing_loops() {
+        // The loops overflow on the way to the answer, so they fault, or run the slow way to wrap
+        // or saturate like they do without the optimizer.
+        let divide = DIVIDE.replace("muli 5 256 5", "muli 5 200 5");
+        // Squaring the ip to leave wraps around to the start in 8 bits.
+        let divisor_sum = &DIVISOR_SUM.replace("mulr 4 4 4", "seti 99 0 4");
+        for overflow in [Overflow::Trapping, Overflow::Wrapping, Overflow::Saturating] {
+            check_same_u8(&divide, 0, [0, 0, 210, 0, 0, 0], overflow);
+            check_same_u8(&divide, 0, [0, 0, 150, 0, 0, 0], overflow);
+            check_same_u8(divisor_sum, 1, [0, 0, 0, 230, 0, 0], overflow);
+            check_same_u8(divisor_sum, 1, [250, 0, 0, 12, 0, 0], overflow);
+            check_same_u8(divisor_sum, 1, [0, 0, 0, 12, 0, 0], overflow);
+        }
+        let program: Program<u8> = Program::parse(&divide, 6).unwrap();
+        let mut machine: Machine<u8, 6> = Machine::new(program.optimize()).unwrap();
+        machine.registers_mut()[2] = 210;
+        let fault = Fault {
+            ip: 1,
+            kind: FaultKind::Overflow,
+        };
+        assert_eq!(Err(fault), machine.run());
+    }
+
+    #[test]
+    fn test_missing_registers() {
+        // A register the macro uses isn't there, so it faults like the instruction does.
+        let program: Program<u64> = Program::parse(DIVIDE, 6).unwrap();
+        let mut plain: Machine<u64, 5> = Machine::new(program.clone()).unwrap();
+        let mut optimized: Machine<u64, 5> = Machine::new(program.optimize()).unwrap();
+        let fault = Fault {
+            ip: 1,
+            kind: FaultKind::NoRegister(5),
+        };
+        assert_eq!(Err(fault), plain.run());
+        assert_eq!(Err(fault), optimized.run());
+    }
+
+    #[test]
+    fn test_nothing_to_optimize() {
+        let program: Program<u64> = Program::parse("seti 5 0 1\naddi 1 1 1", 6).unwrap();
+        assert_eq!(0, program.optimize().macros().count());
+        // Something like the division, but counting down.
+        let program: Program<u64> =
+            Program::parse(&DIVIDE.replace("addi 1 1 1", "addi 1 2 1"), 6).unwrap();
+        assert_eq!(0, program.optimize().macros().count());
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Reads programs written the way the puzzles write them:
+//!
+//! ```text
+//! #ip 0
+//! seti 5 0 1
+//! addi 0 1 0
+//! ```
+
+use crate::{Instruction, Opcode, Operand, Program, Word};
+use aoc_common::{numbered_lines, parse_number, ParseError};
+use std::fmt;
+
+/// Checks that `text` names one of the registers.
+fn parse_register(
+    line_no: usize,
+    line: &str,
+    text: &str,
+    registers: usize,
+) -> Result<usize, ParseError> {
+    let register: usize = parse_number(line_no, line, text)?;
+    if register >= registers {
+        let message = format!("there are only {} registers", registers);
+        return Err(ParseError::at(line_no, line, text, &message));
+    }
+    Ok(register)
+}
+
+/// Parses an operand, which has to be a register if the opcode uses it as one.
+fn parse_operand<W: Word>(
+    line_no: usize,
+    line: &str,
+    text: &str,
+    operand: Operand,
+    registers: usize,
+) -> Result<W, ParseError> {
+    if operand == Operand::Register {
+        parse_register(line_no, line, text, registers)?;
+    }
+    parse_number(line_no, line, text)
+}
+
+impl<W: Word> Program<W> {
+    /// Parses a program for a machine with `registers` registers.  The instruction pointer is
+    /// bound with a `#ip` line before the instructions, or not at all.
+    pub fn parse(input: &str, registers: usize) -> Result<Program<W>, ParseError> {
+        let mut ip_reg = None;
+        let mut instructions = Vec::new();
+        for (line_no, line) in numbered_lines(input) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => continue,
+                ["#ip", reg] => {
+                    if !instructions.is_empty() || ip_reg.is_some() {
+                        let message = "the ip can only be bound once, before the instructions";
+                        return Err(ParseError::line(line_no, line, message));
+                    }
+                    ip_reg = Some(parse_register(line_no, line, reg, registers)?);
+                }
+                [mnemonic, a, b, c] => {
+                    let op = Opcode::from_mnemonic(mnemonic)
+                        .ok_or_else(|| ParseError::at(line_no, line, mnemonic, "unknown opcode"))?;
+                    let (a_operand, b_operand) = op.operands();
+                    instructions.push(Instruction::new(
+                        op,
+                        parse_operand(line_no, line, a, a_operand, registers)?,
+                        parse_operand(line_no, line, b, b_operand, registers)?,
+                        parse_operand(line_no, line, c, Operand::Register, registers)?,
+                    ));
+                }
+                _ => {
+                    let message = "expected an opcode and three operands";
+                    return Err(ParseError::line(line_no, line, message));
+                }
+            }
+        }
+        Ok(Program::new(ip_reg, instructions))
+    }
+}
+
+/// Writes the program the way `parse` reads it.
+impl<W: Word> fmt::Display for Program<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ip_reg) = self.ip_reg() {
+            writeln!(f, "#ip {}", ip_reg)?;
+        }
+        for ins in self.instructions() {
+            writeln!(f, "{}", ins)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Machine;
+
+    #[test]
+    fn test_parse() {
+        // The example from day 19.
+        let input = "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5
+";
+        let program: Program<u64> = Program::parse(input, 6).unwrap();
+        assert_eq!(Some(0), program.ip_reg());
+        assert_eq!(7, program.instructions().len());
+        assert_eq!(
+            Instruction::new(Opcode::addi, 0, 1, 0),
+            program.instructions()[2]
+        );
+        let mut machine: Machine<u64, 6> = Machine::new(program).unwrap();
+        machine.run().unwrap();
+        assert_eq!([6, 5, 6, 0, 0, 9], *machine.registers());
+
+        let program: Program<u16> = Program::parse("\nseti 5 0 1\n", 4).unwrap();
+        assert_eq!(None, program.ip_reg());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Program::<u64>::parse("#ip 0\nsetx 5 0 1", 6).unwrap_err();
+        assert_eq!("2:1: unknown opcode: \"setx\"", err.to_string());
+        let err = Program::<u64>::parse("seti 5 0", 6).unwrap_err();
+        assert_eq!(
+            "1:1: expected an opcode and three operands: \"seti 5 0\"",
+            err.to_string()
+        );
+        let err = Program::<u64>::parse("seti 5 0 x", 6).unwrap_err();
+        assert_eq!("1:10: not a number: \"x\"", err.to_string());
+        // seti's a is a value, but addi's is a register.
+        assert!(Program::<u64>::parse("seti 9 0 1", 6).is_ok());
+        let err = Program::<u64>::parse("addi 9 0 1", 6).unwrap_err();
+        assert_eq!("1:6: there are only 6 registers: \"9\"", err.to_string());
+        let err = Program::<u64>::parse("#ip 6", 6).unwrap_err();
+        assert_eq!((1, 5), (err.line, err.column));
+        let err = Program::<u64>::parse("seti 5 0 1\n#ip 0", 6).unwrap_err();
+        assert_eq!(2, err.line);
+        let err = Program::<u16>::parse("seti 70000 0 1", 4).unwrap_err();
+        assert_eq!("1:6: not a number: \"70000\"", err.to_string());
+    }
+}
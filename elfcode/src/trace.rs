@@ -0,0 +1,416 @@
+// Copyright 2018 Marie Janssen.  All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be found in the LICENSE file.
+
+//! Recording every step a machine takes, and comparing two recordings.
+//!
+//! A trace starts with the registers the machine started with.  Then for each step there's the
+//! ip, the instruction (or a mark for a macro-instruction) and the registers it changed, with
+//! numbers written seven bits to a byte, so a long run doesn't take up much room.
+
+use crate::{Instruction, Machine, Opcode, Word};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+const MAGIC: &[u8; 4] = b"ELFT";
+const VERSION: u8 = 1;
+/// Instead of an opcode, for a step that ran a macro-instruction.
+const MACRO: u8 = 16;
+/// Instead of a register, for a program that doesn't bind the ip.
+const NO_IP_REG: u8 = 255;
+
+fn write_number<Wr: Write>(out: &mut Wr, mut n: u64) -> io::Result<()> {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            return out.write_all(&[byte]);
+        }
+        out.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_byte<R: Read>(input: &mut R) -> io::Result<u8> {
+    let mut byte = [0];
+    input.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_number<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut n = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(input)?;
+        n |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(invalid("number too long"))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Runs a machine and writes down what it does.
+pub struct Recorder<Wr> {
+    out: Wr,
+    steps: usize,
+}
+
+impl<Wr: Write> Recorder<Wr> {
+    /// Starts a trace of `machine` from where it is now.
+    pub fn new<W: Word, const N: usize>(
+        mut out: Wr,
+        machine: &Machine<W, N>,
+    ) -> io::Result<Recorder<Wr>> {
+        assert!(N < NO_IP_REG as usize, "Too many registers to trace");
+        out.write_all(MAGIC)?;
+        let ip_reg = machine.program().ip_reg().map_or(NO_IP_REG, |r| r as u8);
+        out.write_all(&[VERSION, N as u8, ip_reg])?;
+        for value in machine.registers() {
+            write_number(&mut out, value.to_index() as u64)?;
+        }
+        Ok(Recorder { out, steps: 0 })
+    }
+
+    /// Runs one step of `machine` and records it.  Returns false without recording anything if
+    /// the program has halted, and an error if the step faults.
+    pub fn step<W: Word, const N: usize>(
+        &mut self,
+        machine: &mut Machine<W, N>,
+    ) -> io::Result<bool> {
+        let ip = machine.ip();
+        let instruction = match (machine.next_macro(), machine.next_instruction()) {
+            (Some(_), _) => None,
+            (None, Some(&instruction)) => Some(instruction),
+            (None, None) => return Ok(false),
+        };
+        let before = *machine.registers();
+        machine.step().map_err(io::Error::other)?;
+
+        write_number(&mut self.out, ip as u64)?;
+        match instruction {
+            Some(ins) => {
+                self.out.write_all(&[ins.op as u8])?;
+                for operand in [ins.a, ins.b, ins.c] {
+                    write_number(&mut self.out, operand.to_index() as u64)?;
+                }
+            }
+            None => self.out.write_all(&[MACRO])?,
+        }
+        let after = machine.registers();
+        let changed: Vec<usize> = (0..N).filter(|&r| before[r] != after[r]).collect();
+        self.out.write_all(&[changed.len() as u8])?;
+        for r in changed {
+            self.out.write_all(&[r as u8])?;
+            write_number(&mut self.out, after[r].to_index() as u64)?;
+        }
+        self.steps += 1;
+        Ok(true)
+    }
+
+    /// Runs `machine` until it halts, recording every step, and returns how many steps that took.
+    pub fn run<W: Word, const N: usize>(
+        &mut self,
+        machine: &mut Machine<W, N>,
+    ) -> io::Result<usize> {
+        let start = self.steps;
+        while self.step(machine)? {}
+        Ok(self.steps - start)
+    }
+
+    /// Flushes the trace, and hands back where it went.
+    pub fn finish(mut self) -> io::Result<Wr> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// One step from a trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub ip: usize,
+    /// What ran, or None for a macro-instruction.
+    pub instruction: Option<Instruction<u64>>,
+    /// All the registers after the step.
+    pub registers: Vec<u64>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.instruction {
+            Some(ins) => write!(f, "ip={} {} {:?}", self.ip, ins, self.registers),
+            None => write!(f, "ip={} (macro) {:?}", self.ip, self.registers),
+        }
+    }
+}
+
+/// Reads a trace back a step at a time.
+pub struct TraceReader<R> {
+    input: R,
+    ip_reg: Option<usize>,
+    start: Vec<u64>,
+    registers: Vec<u64>,
+}
+
+impl<R: Read> TraceReader<R> {
+    /// Reads the start of the trace.
+    pub fn new(mut input: R) -> io::Result<TraceReader<R>> {
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not an ElfCode trace"));
+        }
+        if read_byte(&mut input)? != VERSION {
+            return Err(invalid("unknown trace version"));
+        }
+        let registers = read_byte(&mut input)? as usize;
+        let ip_reg = match read_byte(&mut input)? {
+            NO_IP_REG => None,
+            r => Some(r as usize),
+        };
+        let start = (0..registers)
+            .map(|_| read_number(&mut input))
+            .collect::<io::Result<Vec<u64>>>()?;
+        Ok(TraceReader {
+            input,
+            ip_reg,
+            registers: start.clone(),
+            start,
+        })
+    }
+
+    /// The register the ip was bound to.
+    pub fn ip_reg(&self) -> Option<usize> {
+        self.ip_reg
+    }
+
+    /// The registers before the first step.
+    pub fn start(&self) -> &[u64] {
+        &self.start
+    }
+
+    fn entry(&mut self, ip: u64) -> io::Result<TraceEntry> {
+        let input = &mut self.input;
+        let instruction = match read_byte(input)? {
+            MACRO => None,
+            op if (op as usize) < Opcode::ALL.len() => Some(Instruction::new(
+                Opcode::ALL[op as usize],
+                read_number(input)?,
+                read_number(input)?,
+                read_number(input)?,
+            )),
+            _ => return Err(invalid("unknown opcode")),
+        };
+        for _ in 0..read_byte(input)? {
+            let r = read_byte(input)? as usize;
+            let value = read_number(input)?;
+            *self
+                .registers
+                .get_mut(r)
+                .ok_or_else(|| invalid("no such register"))? = value;
+        }
+        Ok(TraceEntry {
+            ip: ip as usize,
+            instruction,
+            registers: self.registers.clone(),
+        })
+    }
+}
+
+impl<R: Read> Iterator for TraceReader<R> {
+    type Item = io::Result<TraceEntry>;
+
+    fn next(&mut self) -> Option<io::Result<TraceEntry>> {
+        // The trace can only end between steps.
+        let mut first = [0];
+        match self.input.read(&mut first) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => return Some(Err(e)),
+        }
+        let ip = if first[0] & 0x80 == 0 {
+            Ok(u64::from(first[0]))
+        } else {
+            read_number(&mut self.input).map(|rest| u64::from(first[0] & 0x7f) | rest << 7)
+        };
+        Some(ip.and_then(|ip| self.entry(ip)))
+    }
+}
+
+/// What to compare in `diff`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compare {
+    /// The ip, the instruction and the registers.
+    Everything,
+    /// Only where the programs went, for runs that start with different registers.
+    Flow,
+}
+
+/// Where two traces first differ.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// How many steps they agreed on first.  0 with no entries means they started differently.
+    pub step: usize,
+    /// The steps where they differ, or None where a trace ended.
+    pub left: Option<TraceEntry>,
+    pub right: Option<TraceEntry>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let side = |entry: &Option<TraceEntry>| match entry {
+            Some(entry) => entry.to_string(),
+            None => "halted".to_string(),
+        };
+        if self.left.is_none() && self.right.is_none() {
+            return write!(f, "the traces start with different registers");
+        }
+        write!(
+            f,
+            "after {} steps the same:\n< {}\n> {}",
+            self.step,
+            side(&self.left),
+            side(&self.right)
+        )
+    }
+}
+
+/// Finds the first step where two traces differ, or None if they're the same.
+pub fn diff<A: Read, B: Read>(
+    mut left: TraceReader<A>,
+    mut right: TraceReader<B>,
+    compare: Compare,
+) -> io::Result<Option<Divergence>> {
+    if compare == Compare::Everything && left.start() != right.start() {
+        return Ok(Some(Divergence {
+            step: 0,
+            left: None,
+            right: None,
+        }));
+    }
+    let mut step = 0;
+    loop {
+        let (l, r) = (left.next().transpose()?, right.next().transpose()?);
+        let same = match (&l, &r) {
+            (None, None) => return Ok(None),
+            (Some(l), Some(r)) => match compare {
+                Compare::Everything => l == r,
+                Compare::Flow => l.ip == r.ip && l.instruction == r.instruction,
+            },
+            _ => false,
+        };
+        if !same {
+            return Ok(Some(Divergence {
+                step,
+                left: l,
+                right: r,
+            }));
+        }
+        step += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Program;
+
+    const DAY19: &str = include_str!("../../day19/input.txt");
+
+    /// Records the day 19 program adding up the divisors of `r3`, skipping the setup which would
+    /// make it a big number.
+    fn record(r3: u64, optimize: bool) -> (Vec<u8>, Machine<u64, 6>) {
+        let mut program: Program<u64> = Program::parse(DAY19, 6).unwrap();
+        if optimize {
+            program = program.optimize();
+        }
+        let mut machine = Machine::new(program).unwrap();
+        machine.registers_mut()[3] = r3;
+        machine.set_ip(1);
+        let mut recorder = Recorder::new(Vec::new(), &machine).unwrap();
+        recorder.run(&mut machine).unwrap();
+        (recorder.finish().unwrap(), machine)
+    }
+
+    #[test]
+    fn test_numbers() {
+        let mut out = Vec::new();
+        for &n in &[0, 127, 128, 300, u64::MAX] {
+            write_number(&mut out, n).unwrap();
+        }
+        assert_eq!(1 + 1 + 2 + 2 + 10, out.len());
+        let mut input = &out[..];
+        for &n in &[0, 127, 128, 300, u64::MAX] {
+            assert_eq!(n, read_number(&mut input).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let (trace, machine) = record(10, true);
+        let reader = TraceReader::new(&trace[..]).unwrap();
+        assert_eq!(Some(4), reader.ip_reg());
+        assert_eq!(&[0, 0, 0, 10, 0, 0], reader.start());
+        let entries: Vec<TraceEntry> = reader.collect::<io::Result<_>>().unwrap();
+        assert_eq!(3, entries.len());
+        assert_eq!(
+            "ip=1 seti 1 2 5 [0, 0, 0, 10, 1, 1]",
+            entries[0].to_string()
+        );
+        assert_eq!(
+            "ip=2 (macro) [18, 11, 1, 10, 15, 11]",
+            entries[1].to_string()
+        );
+        assert_eq!(
+            &machine.registers()[..],
+            &entries.last().unwrap().registers[..]
+        );
+
+        assert!(TraceReader::new(&b"ELFX"[..]).is_err());
+        let mut cut = TraceReader::new(&trace[..trace.len() - 1]).unwrap();
+        assert!(cut.any(|entry| entry.is_err()));
+    }
+
+    #[test]
+    fn test_diff() {
+        let (plain, _) = record(10, false);
+        let (optimized, _) = record(10, true);
+        fn read(trace: &[u8]) -> TraceReader<&[u8]> {
+            TraceReader::new(trace).unwrap()
+        }
+        assert_eq!(
+            None,
+            diff(read(&plain), read(&plain), Compare::Everything).unwrap()
+        );
+
+        // The optimized run does the loop all at once.
+        let divergence = diff(read(&plain), read(&optimized), Compare::Everything)
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, divergence.left.unwrap().ip);
+        assert_eq!(None, divergence.right.unwrap().instruction);
+
+        let (twelve, _) = record(12, false);
+        let divergence = diff(read(&plain), read(&twelve), Compare::Everything)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            "the traces start with different registers",
+            divergence.to_string()
+        );
+        // 1 * 10 is 10, so that one adds 1 to r0, and the other goes round again.
+        let divergence = diff(read(&plain), read(&twelve), Compare::Flow)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (Some(7), Some(6)),
+            (
+                divergence.left.map(|entry| entry.ip),
+                divergence.right.map(|entry| entry.ip)
+            )
+        );
+    }
+}